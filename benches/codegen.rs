@@ -0,0 +1,115 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use psf2verilog::{Options, PSF};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::{self, Cursor};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts every allocation, growth included, so the table parsers can be compared by how
+/// often they allocate as well as by time.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// A PSF2 font of random glyphs whose table maps each glyph to `codepoints` code points.
+fn psf2_font(glyphs: u32, width: u32, height: u32, codepoints: u32) -> Vec<u8> {
+    let charsize = width.div_ceil(8) * height;
+    let mut font = vec![0x72, 0xb5, 0x4a, 0x86];
+    for field in [0, 32, 1, glyphs, charsize, height, width] {
+        font.extend_from_slice(&u32::to_le_bytes(field));
+    }
+    let mut state = 0x2545_f491u32;
+    for _ in 0..glyphs * charsize {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        font.push(state as u8);
+    }
+    for glyph in 0..glyphs {
+        for n in 0..codepoints {
+            if let Some(c) = char::from_u32(glyph * codepoints + n + 0x20) {
+                let mut buf = [0u8; 4];
+                font.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+        font.push(0xFF);
+    }
+    font
+}
+
+fn bench_font(c: &mut Criterion, name: &str, font: &[u8]) {
+    let opts = Options::default();
+    c.bench_function(&format!("parse_reader {}", name), |b| {
+        b.iter(|| PSF::parse_reader(Cursor::new(black_box(font))).unwrap())
+    });
+    let psf = PSF::parse_reader(Cursor::new(font)).unwrap();
+    c.bench_function(&format!("write_verilog {}", name), |b| {
+        b.iter(|| psf.write_verilog(&opts, &mut io::sink()).unwrap())
+    });
+    let array_opts = Options {
+        emit_initial_array: true,
+        ..Options::default()
+    };
+    c.bench_function(&format!("write_verilog initial array {}", name), |b| {
+        b.iter(|| psf.write_verilog(&array_opts, &mut io::sink()).unwrap())
+    });
+}
+
+/// Parses the font with the default and the `--compact-table` parser, printing how many
+/// allocations each makes.
+fn bench_table(c: &mut Criterion, name: &str, font: &[u8]) {
+    for (parser, compact_table) in [("simple", false), ("compact", true)] {
+        let opts = Options {
+            compact_table,
+            ..Options::default()
+        };
+        let parse = || PSF::parse_reader_with_warnings(Cursor::new(black_box(font)), &opts);
+        println!(
+            "{} table parser, {}: {} allocations",
+            parser,
+            name,
+            allocations(|| parse().unwrap())
+        );
+        c.bench_function(&format!("parse_reader {} table {}", parser, name), |b| {
+            b.iter(|| parse().unwrap())
+        });
+    }
+}
+
+fn benches(c: &mut Criterion) {
+    bench_font(c, "8x16 256 glyphs", &psf2_font(256, 8, 16, 1));
+    bench_font(c, "12x24 16384 glyphs", &psf2_font(16384, 12, 24, 1));
+    bench_table(c, "8x16 256 glyphs", &psf2_font(256, 8, 16, 1));
+    bench_table(
+        c,
+        "8x8 60000 glyphs of 5 code points",
+        &psf2_font(60000, 8, 8, 5),
+    );
+}
+
+criterion_group! {
+    name = codegen;
+    config = Criterion::default().sample_size(20);
+    targets = benches
+}
+criterion_main!(codegen);
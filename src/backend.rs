@@ -0,0 +1,227 @@
+use super::{Format, Lang, Options, PSF};
+use std::io::{self, Write};
+
+pub trait Backend: Sync {
+    fn name(&self) -> &'static str;
+    /// The `--lang` the backend writes; those writing Verilog are picked by `--format`.
+    fn lang(&self) -> Lang {
+        Lang::Verilog
+    }
+    fn emit(&self, psf: &PSF, opts: &Options, out: &mut dyn Write) -> io::Result<()>;
+}
+
+struct Verilog;
+struct Memh;
+struct BytePort;
+struct RawHex;
+struct Oneline;
+struct Mif;
+struct Coe;
+struct Vhdl;
+struct Python;
+struct Scala;
+#[cfg(feature = "json")]
+struct Json;
+
+impl Backend for Verilog {
+    fn name(&self) -> &'static str {
+        "verilog"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_header_file(opts)?;
+        psf.write_unmapped_comment(opts, &mut out)?;
+        psf.write_table_comment(opts, &mut out)?;
+        psf.write_rom(opts, &mut out)
+    }
+}
+
+impl Backend for Memh {
+    fn name(&self) -> &'static str {
+        "memh"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_header_file(opts)?;
+        psf.write_unmapped_comment(opts, &mut out)?;
+        psf.write_table_comment(opts, &mut out)?;
+        psf.write_memh_rom(opts, &mut out)
+    }
+}
+
+impl Backend for BytePort {
+    fn name(&self) -> &'static str {
+        "byte-port"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_header_file(opts)?;
+        psf.write_unmapped_comment(opts, &mut out)?;
+        psf.write_table_comment(opts, &mut out)?;
+        psf.write_byte_port(opts, &mut out)
+    }
+}
+
+impl Backend for RawHex {
+    fn name(&self) -> &'static str {
+        "rawhex"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_rawhex(opts, &mut out)
+    }
+}
+
+impl Backend for Oneline {
+    fn name(&self) -> &'static str {
+        "oneline"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_oneline(opts, &mut out)
+    }
+}
+
+impl Backend for Mif {
+    fn name(&self) -> &'static str {
+        "mif"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        if !opts.no_wrapper {
+            psf.write_header_file(opts)?;
+            psf.write_unmapped_comment(opts, &mut out)?;
+            psf.write_table_comment(opts, &mut out)?;
+        }
+        psf.write_mif_rom(opts, &mut out)
+    }
+}
+
+impl Backend for Coe {
+    fn name(&self) -> &'static str {
+        "coe"
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_coe(opts, &mut out)
+    }
+}
+
+impl Backend for Vhdl {
+    fn name(&self) -> &'static str {
+        "vhdl"
+    }
+
+    fn lang(&self) -> Lang {
+        Lang::Vhdl
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_vhdl(opts, &mut out)
+    }
+}
+
+impl Backend for Python {
+    fn name(&self) -> &'static str {
+        "python"
+    }
+
+    fn lang(&self) -> Lang {
+        Lang::Python
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_python(opts, &mut out)
+    }
+}
+
+impl Backend for Scala {
+    fn name(&self) -> &'static str {
+        "scala"
+    }
+
+    fn lang(&self) -> Lang {
+        Lang::Scala
+    }
+
+    fn emit(&self, psf: &PSF, opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_scala(opts, &mut out)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Backend for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn emit(&self, psf: &PSF, _opts: &Options, mut out: &mut dyn Write) -> io::Result<()> {
+        psf.write_json(&mut out)
+    }
+}
+
+pub static BACKENDS: &[&dyn Backend] = &[
+    &Verilog,
+    &Memh,
+    &BytePort,
+    &RawHex,
+    &Oneline,
+    &Mif,
+    &Coe,
+    &Vhdl,
+    &Python,
+    &Scala,
+    #[cfg(feature = "json")]
+    &Json,
+];
+
+/// The registered backend called `name`, if any.
+pub fn named(name: &str) -> Option<&'static dyn Backend> {
+    BACKENDS
+        .iter()
+        .find(|backend| backend.name() == name)
+        .copied()
+}
+
+/// The `--format` values: the names of the Verilog backends, in registry order.
+pub fn format_names() -> impl Iterator<Item = &'static str> {
+    BACKENDS
+        .iter()
+        .filter(|backend| backend.lang() == Lang::Verilog)
+        .map(|backend| backend.name())
+}
+
+/// Every language a registered backend writes, in registry order.
+pub fn langs() -> Vec<Lang> {
+    let mut langs = vec![];
+    for backend in BACKENDS {
+        if !langs.contains(&backend.lang()) {
+            langs.push(backend.lang());
+        }
+    }
+    langs
+}
+
+pub(crate) fn for_format(format: Format) -> &'static dyn Backend {
+    match format {
+        Format::Verilog => &Verilog,
+        Format::Memh => &Memh,
+        Format::BytePort => &BytePort,
+        Format::RawHex => &RawHex,
+        Format::Oneline => &Oneline,
+        Format::Mif => &Mif,
+        Format::Coe => &Coe,
+        #[cfg(feature = "json")]
+        Format::Json => &Json,
+    }
+}
+
+/// The backend selected by `--lang` and, for Verilog, `--format`.
+pub fn backend(opts: &Options) -> &'static dyn Backend {
+    match opts.lang {
+        Lang::Verilog => for_format(opts.format),
+        Lang::Vhdl => &Vhdl,
+        Lang::Python => &Python,
+        Lang::Scala => &Scala,
+    }
+}
@@ -0,0 +1,142 @@
+use super::{ParseError, TableEntry, Version, PSF};
+use std::convert::TryFrom;
+
+struct BoundingBox {
+    width: i32,
+    height: i32,
+    x_offset: i32,
+    y_offset: i32,
+}
+
+impl BoundingBox {
+    fn parse(fields: &[&str]) -> Result<Self, ParseError> {
+        let numbers = fields
+            .iter()
+            .map(|field| field.parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ParseError::MalformedBDF(fields.join(" ")))?;
+        match numbers.as_slice() {
+            &[width, height, x_offset, y_offset] if width >= 0 && height >= 0 => Ok(Self {
+                width,
+                height,
+                x_offset,
+                y_offset,
+            }),
+            _ => Err(ParseError::MalformedBDF(fields.join(" "))),
+        }
+    }
+}
+
+pub(super) fn parse(text: &str) -> Result<PSF, ParseError> {
+    let mut font_bbx = None;
+    let mut bitmap = vec![];
+    let mut table = vec![];
+    let mut stride = 0;
+    let mut charsize = 0;
+
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.first() {
+            Some(&"FONTBOUNDINGBOX") => {
+                let bbx = BoundingBox::parse(&fields[1..])?;
+                // Every glyph is allocated at this size, so a box no text could fill is refused
+                let needed = (bbx.width as u64).div_ceil(8) * bbx.height as u64;
+                if needed > text.len() as u64 {
+                    return Err(ParseError::MalformedBDF(format!(
+                        "FONTBOUNDINGBOX {}x{} is larger than the {}-byte file",
+                        bbx.width,
+                        bbx.height,
+                        text.len()
+                    )));
+                }
+                PSF::checked_geometry(bbx.width as u32, bbx.height as u32, needed as u32)?;
+                stride = (bbx.width as usize).div_ceil(8);
+                charsize = needed as usize;
+                font_bbx = Some(bbx);
+            }
+            Some(&"STARTCHAR") => {
+                let font_bbx = font_bbx.as_ref().ok_or_else(|| {
+                    ParseError::MalformedBDF("STARTCHAR before FONTBOUNDINGBOX".to_string())
+                })?;
+                let mut glyph = vec![0u8; charsize];
+                let mut entry = TableEntry::default();
+                let mut glyph_bbx = None;
+                while let Some(line) = lines.next() {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    match fields.first() {
+                        Some(&"ENCODING") => {
+                            let encoding =
+                                fields.get(1).and_then(|field| field.parse::<i64>().ok());
+                            if let Some(c) = encoding
+                                .and_then(|encoding| u32::try_from(encoding).ok())
+                                .and_then(char::from_u32)
+                            {
+                                entry.represented.push(c);
+                            }
+                        }
+                        Some(&"BBX") => glyph_bbx = Some(BoundingBox::parse(&fields[1..])?),
+                        Some(&"BITMAP") => {
+                            let bbx = glyph_bbx.as_ref().unwrap_or(font_bbx);
+                            // Offsets in i64, as the i32 fields of a hostile file can overflow
+                            let left = bbx.x_offset as i64 - font_bbx.x_offset as i64;
+                            let top = (font_bbx.height as i64 + font_bbx.y_offset as i64)
+                                - (bbx.y_offset as i64 + bbx.height as i64);
+                            for row in 0..bbx.height as i64 {
+                                let line = lines.next().ok_or_else(|| {
+                                    ParseError::MalformedBDF("truncated BITMAP".to_string())
+                                })?;
+                                let bytes = decode_hex_row(line.trim())?;
+                                for col in 0..(bbx.width as i64).min(bytes.len() as i64 * 8) {
+                                    let set = bytes[col as usize / 8] & (0x80 >> (col % 8)) != 0;
+                                    let (x, y) = (left + col, top + row);
+                                    if set
+                                        && x >= 0
+                                        && x < font_bbx.width as i64
+                                        && y >= 0
+                                        && y < font_bbx.height as i64
+                                    {
+                                        glyph[y as usize * stride + x as usize / 8] |=
+                                            0x80 >> (x % 8);
+                                    }
+                                }
+                            }
+                        }
+                        Some(&"ENDCHAR") => break,
+                        _ => {}
+                    }
+                }
+                bitmap.append(&mut glyph);
+                table.push(entry);
+            }
+            _ => {}
+        }
+    }
+
+    let font_bbx =
+        font_bbx.ok_or_else(|| ParseError::MalformedBDF("missing FONTBOUNDINGBOX".to_string()))?;
+    if table.is_empty() {
+        return Err(ParseError::MalformedBDF("no glyphs".to_string()));
+    }
+    Ok(PSF {
+        version: Version::Bdf,
+        glyph_count: table.len() as u32,
+        charsize: charsize as u32,
+        height: font_bbx.height as u32,
+        width: font_bbx.width as u32,
+        bitmap,
+        table: Some(table),
+        has_sequences: false,
+    })
+}
+
+fn decode_hex_row(row: &str) -> Result<Vec<u8>, ParseError> {
+    (0..row.len())
+        .step_by(2)
+        .map(|i| {
+            row.get(i..(i + 2).min(row.len()))
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| ParseError::MalformedBDF(format!("bad BITMAP row {}", row)))
+        })
+        .collect()
+}
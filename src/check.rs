@@ -0,0 +1,102 @@
+use super::{Options, ParseError, Warning, PSF};
+
+impl PSF {
+    /// Runs every consistency check on a parsed font, naming each one with its outcome.
+    pub fn check(
+        &self,
+        warnings: &[Warning],
+        opts: &Options,
+    ) -> Vec<(&'static str, Result<(), String>)> {
+        let expected_charsize = self.height * (self.width.div_ceil(8));
+        let table_length = self
+            .table
+            .as_ref()
+            .map_or(self.glyph_count as usize, Vec::len);
+        let of_kind = |header: bool| -> Result<(), String> {
+            let found: Vec<String> = warnings
+                .iter()
+                .filter(|warning| {
+                    header == matches!(warning, Warning::ShortHeader(_) | Warning::UnknownFlags(_))
+                        && !matches!(warning, Warning::PaddedGlyphs(..))
+                })
+                .map(ToString::to_string)
+                .collect();
+            if found.is_empty() {
+                Ok(())
+            } else {
+                Err(found.join("; "))
+            }
+        };
+        vec![
+            ("header", of_kind(true)),
+            (
+                "geometry",
+                if self.width > 0 && self.height > 0 {
+                    Ok(())
+                } else {
+                    Err(format!("{}x{} glyphs", self.width, self.height))
+                },
+            ),
+            (
+                "glyph count",
+                if self.glyph_count > 0 {
+                    Ok(())
+                } else {
+                    Err("the font has no glyphs".to_string())
+                },
+            ),
+            (
+                "charsize",
+                if self.charsize >= expected_charsize {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} bytes per glyph, {}x{} needs {}",
+                        self.charsize, self.width, self.height, expected_charsize
+                    ))
+                },
+            ),
+            (
+                "table length",
+                if table_length == self.glyph_count as usize {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} entries for {} glyphs",
+                        table_length, self.glyph_count
+                    ))
+                },
+            ),
+            ("file length", of_kind(false)),
+            (
+                "options",
+                self.check_options(opts).map_err(|err| match err {
+                    ParseError::InvalidArgument(message) => message,
+                    err => format!("{:?}", err),
+                }),
+            ),
+        ]
+    }
+
+    /// Every glyph and row with a set bit in the padding past `width` at the end of the row,
+    /// as (glyph, row, padding bits), the bits right-aligned.
+    pub fn padding_offenders(&self) -> Vec<(usize, usize, u8)> {
+        let stride = self.width.div_ceil(8) as usize;
+        let unused = stride as u32 * 8 - self.width;
+        if unused == 0 {
+            return vec![];
+        }
+        let mask = ((1u16 << unused) - 1) as u8;
+        let mut offenders = vec![];
+        for glyph in 0..self.glyph_count as usize {
+            let bytes = &self.bitmap[glyph * self.charsize as usize..][..self.pixel_bytes()];
+            for (row, row_bytes) in bytes.chunks_exact(stride).enumerate() {
+                let padding = row_bytes[stride - 1] & mask;
+                if padding != 0 {
+                    offenders.push((glyph, row, padding));
+                }
+            }
+        }
+        offenders
+    }
+}
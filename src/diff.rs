@@ -0,0 +1,111 @@
+use super::verilog::{address_width, write_rom_module};
+use super::{Options, ParseError, PSF};
+use std::io::{self, Write};
+
+impl PSF {
+    fn glyph_differs(&self, other: &PSF, index: usize) -> bool {
+        let (a, b) = (self.glyph(index), other.glyph(index));
+        (0..a.height()).any(|row| a.row_bits(row).ne(b.row_bits(row)))
+    }
+
+    /// Writes the indices of glyphs that differ from `other`, optionally drawing each pair
+    /// side by side, and returns how many differ. Fonts of different geometry are reported as
+    /// such and count as differing in every glyph.
+    pub fn write_diff<W: Write>(&self, other: &PSF, show: bool, out: &mut W) -> io::Result<usize> {
+        if (self.width, self.height) != (other.width, other.height) {
+            writeln!(
+                out,
+                "geometry differs: {}x{} vs {}x{}",
+                self.width, self.height, other.width, other.height
+            )?;
+            return Ok(self.glyph_count.max(other.glyph_count) as usize);
+        }
+        let common = self.glyph_count.min(other.glyph_count) as usize;
+        let mut differing = 0;
+        for index in 0..common {
+            if !self.glyph_differs(other, index) {
+                continue;
+            }
+            let (a, b) = (self.glyph(index), other.glyph(index));
+            differing += 1;
+            writeln!(out, "glyph {} differs", index)?;
+            if show {
+                for row in 0..a.height() {
+                    let draw = |bits: &mut dyn Iterator<Item = bool>| -> String {
+                        bits.map(|set| if set { '#' } else { '.' }).collect()
+                    };
+                    writeln!(
+                        out,
+                        "    {}  {}",
+                        draw(&mut a.row_bits(row)),
+                        draw(&mut b.row_bits(row))
+                    )?;
+                }
+            }
+        }
+        if self.glyph_count != other.glyph_count {
+            writeln!(
+                out,
+                "glyph count differs: {} vs {}",
+                self.glyph_count, other.glyph_count
+            )?;
+            differing += self.glyph_count.max(other.glyph_count) as usize - common;
+        }
+        writeln!(out, "{} glyphs differ", differing)?;
+        Ok(differing)
+    }
+
+    /// Writes the glyphs of `new` that differ from this base font as a sparse glyphdelta
+    /// ROM, slot S holding the Sth changed glyph, and a glyphdelta_index ROM giving the
+    /// glyph index each slot replaces. Returns the indices of the changed glyphs; when
+    /// there are none, nothing is written.
+    pub fn write_delta<W: Write>(
+        &self,
+        new: &PSF,
+        opts: &Options,
+        out: &mut W,
+    ) -> Result<Vec<usize>, ParseError> {
+        if (self.width, self.height, self.glyph_count) != (new.width, new.height, new.glyph_count) {
+            return Err(ParseError::InvalidArgument(format!(
+                "delta needs fonts of the same geometry, got {} {}x{} glyphs and {} {}x{} glyphs",
+                self.glyph_count, self.width, self.height, new.glyph_count, new.width, new.height
+            )));
+        }
+        let changed: Vec<usize> = (0..self.glyph_count as usize)
+            .filter(|&index| self.glyph_differs(new, index))
+            .collect();
+        if changed.is_empty() {
+            return Ok(changed);
+        }
+        let words = new.glyph_words(opts);
+        let slots: Vec<Vec<bool>> = changed.iter().map(|&index| words[index].clone()).collect();
+        let index_width = address_width(self.glyph_count as usize).max(1);
+        let indices: Vec<Vec<bool>> = changed
+            .iter()
+            .map(|&index| {
+                (0..index_width)
+                    .rev()
+                    .map(|bit| index >> bit & 1 != 0)
+                    .collect()
+            })
+            .collect();
+        writeln!(
+            out,
+            "// {} changed glyphs: write glyphdelta[S] over glyph glyphdelta_index[S] of the base font for S < {}",
+            changed.len(),
+            changed.len()
+        )?;
+        write_rom_module(opts, out, "glyphdelta", "slot", "glyph", &slots, &[])?;
+        writeln!(out)?;
+        write_rom_module(
+            opts,
+            out,
+            "glyphdelta_index",
+            "slot",
+            "codepoint",
+            &indices,
+            &[],
+        )?;
+        Ok(changed)
+    }
+}
@@ -0,0 +1,76 @@
+use super::PSF;
+use std::io::{self, Write};
+
+impl PSF {
+    /// Writes every glyph differing from the `width` by `height` pattern in at most
+    /// `tolerance` pixels, with its distance and code points, and returns how many match.
+    pub fn write_matches<W: Write>(
+        &self,
+        (width, height, pattern): (usize, usize, &[bool]),
+        tolerance: usize,
+        out: &mut W,
+    ) -> io::Result<usize> {
+        if (width, height) != (self.width as usize, self.height as usize) {
+            writeln!(
+                out,
+                "pattern is {}x{} but the glyphs are {}x{}",
+                width, height, self.width, self.height
+            )?;
+            return Ok(0);
+        }
+        let mut matches = 0;
+        for index in 0..self.glyph_count as usize {
+            let glyph = self.glyph(index);
+            let distance = (0..height)
+                .flat_map(|row| glyph.row_bits(row))
+                .zip(pattern)
+                .filter(|&(set, &expected)| set != expected)
+                .count();
+            if distance > tolerance {
+                continue;
+            }
+            matches += 1;
+            write!(out, "glyph {} distance {}", index, distance)?;
+            if let Some(entry) = self.table.as_ref().and_then(|table| table.get(index)) {
+                for c in &entry.represented {
+                    write!(out, " U+{:04X}", *c as u32)?;
+                }
+            }
+            writeln!(out)?;
+        }
+        writeln!(out, "{} glyphs match", matches)?;
+        Ok(matches)
+    }
+
+    /// Draws every glyph the unicode table maps `c` to, or without a table the glyph at index
+    /// `c`, and returns how many there are.
+    pub fn write_query<W: Write>(&self, c: char, out: &mut W) -> io::Result<usize> {
+        let indices: Vec<usize> = match &self.table {
+            Some(table) => table
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.represented.contains(&c))
+                .map(|(index, _)| index)
+                .collect(),
+            None => Some(c as usize)
+                .filter(|&index| index < self.glyph_count as usize)
+                .into_iter()
+                .collect(),
+        };
+        if indices.is_empty() {
+            writeln!(out, "U+{:04X} is not in the font", c as u32)?;
+        }
+        for &index in &indices {
+            writeln!(out, "U+{:04X} glyph {}", c as u32, index)?;
+            let glyph = self.glyph(index);
+            for row in 0..self.height as usize {
+                let line: String = glyph
+                    .row_bits(row)
+                    .map(|set| if set { '#' } else { '.' })
+                    .collect();
+                writeln!(out, "{}", line)?;
+            }
+        }
+        Ok(indices.len())
+    }
+}
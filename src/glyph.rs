@@ -0,0 +1,111 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x_min: usize,
+    pub y_min: usize,
+    pub x_max: usize,
+    pub y_max: usize,
+}
+
+pub struct Glyph<'a> {
+    bytes: &'a [u8],
+    width: usize,
+    height: usize,
+    stride: usize,
+}
+
+impl<'a> Glyph<'a> {
+    pub(crate) fn new(bytes: &'a [u8], width: usize, height: usize) -> Self {
+        Self {
+            bytes,
+            width,
+            height,
+            stride: width.div_ceil(8),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the pixel at `row`, `col` is set. The leftmost pixel of each row is the most
+    /// significant bit of its first byte; the padding bits of the last byte are never read.
+    pub fn pixel(&self, row: usize, col: usize) -> bool {
+        assert!(row < self.height && col < self.width);
+        self.bytes[row * self.stride + col / 8] & (0x80 >> (col % 8)) != 0
+    }
+
+    pub fn row_bits(&self, row: usize) -> impl Iterator<Item = bool> + '_ {
+        (0..self.width).map(move |col| self.pixel(row, col))
+    }
+
+    /// The tightest box holding every set pixel, or None for a blank glyph.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let mut bounds: Option<Bounds> = None;
+        for row in 0..self.height {
+            for (col, set) in self.row_bits(row).enumerate() {
+                if set {
+                    let b = bounds.get_or_insert(Bounds {
+                        x_min: col,
+                        y_min: row,
+                        x_max: col,
+                        y_max: row,
+                    });
+                    b.x_min = b.x_min.min(col);
+                    b.x_max = b.x_max.max(col);
+                    b.y_max = row;
+                }
+            }
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_follow_the_row_stride() {
+        // 10x3, two bytes a row; the low six bits of each second byte are padding and set
+        let bytes = [
+            0b1000_0000,
+            0b0011_1111,
+            0b0000_0001,
+            0b0111_1111,
+            0xFF,
+            0xFF,
+        ];
+        let glyph = Glyph::new(&bytes, 10, 3);
+        let rows: Vec<String> = (0..3)
+            .map(|row| {
+                glyph
+                    .row_bits(row)
+                    .map(|set| if set { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(rows, ["#.........", ".......#.#", "##########"]);
+        assert!(glyph.pixel(0, 0));
+        assert!(!glyph.pixel(0, 9));
+        assert!(glyph.pixel(1, 9));
+        assert_eq!(
+            glyph.bounds(),
+            Some(Bounds {
+                x_min: 0,
+                y_min: 0,
+                x_max: 9,
+                y_max: 2
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn pixel_past_the_width_panics() {
+        Glyph::new(&[0xFF, 0xFF], 10, 1).pixel(0, 10);
+    }
+}
@@ -0,0 +1,193 @@
+use super::verilog::{address_width, blank_literal, blank_word, write_rom_module};
+use super::{Options, PSF};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Multipliers tried for each table size before the table is doubled.
+const ATTEMPTS: usize = 64;
+
+/// The top `bits` bits of the low 32 bits of `key * multiplier`, as the generated Verilog
+/// computes them.
+fn top_bits(key: u32, multiplier: u32, bits: u32) -> usize {
+    (key.wrapping_mul(multiplier) >> (32 - bits)) as usize
+}
+
+/// A hash-and-displace perfect hash: a key's bucket picks a displacement that is XORed into
+/// its first-level slot, the displacements chosen at build time so that no two keys share a
+/// slot.
+struct PerfectHash {
+    slot_multiplier: u32,
+    bucket_multiplier: u32,
+    slot_bits: u32,
+    bucket_bits: u32,
+    displacements: Vec<usize>,
+}
+
+impl PerfectHash {
+    fn slot(&self, key: u32) -> usize {
+        let bucket = top_bits(key, self.bucket_multiplier, self.bucket_bits);
+        top_bits(key, self.slot_multiplier, self.slot_bits) ^ self.displacements[bucket]
+    }
+
+    /// Finds a hash of the distinct `keys` into a table of at least as many slots, a power
+    /// of two, with a bucket for every four keys. Multipliers come from a fixed xorshift
+    /// sequence so the same keys always give the same hash.
+    fn build(keys: &[u32]) -> Self {
+        let bucket_bits = address_width(keys.len().div_ceil(4)).max(1) as u32;
+        let mut slot_bits = address_width(keys.len()).max(1) as u32;
+        let mut state = 0x9E37_79B9u32;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state | 1
+        };
+        loop {
+            for _ in 0..ATTEMPTS {
+                let (slot_multiplier, bucket_multiplier) = (next(), next());
+                let mut buckets = vec![vec![]; 1 << bucket_bits];
+                for &key in keys {
+                    buckets[top_bits(key, bucket_multiplier, bucket_bits)].push(top_bits(
+                        key,
+                        slot_multiplier,
+                        slot_bits,
+                    ));
+                }
+                let mut order: Vec<usize> = (0..buckets.len()).collect();
+                order.sort_by_key(|&bucket| std::cmp::Reverse(buckets[bucket].len()));
+                let mut used = vec![false; 1 << slot_bits];
+                let mut displacements = vec![0; buckets.len()];
+                let placed = order.iter().all(|&bucket| {
+                    let slots = &buckets[bucket];
+                    let fits = (0..used.len()).find(|&displacement| {
+                        slots.iter().enumerate().all(|(i, &slot)| {
+                            !used[slot ^ displacement] && !slots[..i].contains(&slot)
+                        })
+                    });
+                    if let Some(displacement) = fits {
+                        for &slot in slots {
+                            used[slot ^ displacement] = true;
+                        }
+                        displacements[bucket] = displacement;
+                    }
+                    fits.is_some()
+                });
+                if placed {
+                    return PerfectHash {
+                        slot_multiplier,
+                        bucket_multiplier,
+                        slot_bits,
+                        bucket_bits,
+                        displacements,
+                    };
+                }
+            }
+            slot_bits += 1;
+        }
+    }
+}
+
+impl PSF {
+    /// Writes a glyphhash module returning the glyph of a unicode code point, its ROM holding
+    /// only the code points in the table: a perfect hash gives each its own slot, where the
+    /// glyph word is stored with the code point so that any other code point reads 0. A code
+    /// point drawn by several glyphs keeps the first.
+    pub fn write_hashed<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let mut glyphs = BTreeMap::new();
+        for (index, entry) in self.table.iter().flatten().enumerate() {
+            for &c in &entry.represented {
+                glyphs.entry(c as u32).or_insert(index);
+            }
+        }
+        let keys: Vec<u32> = glyphs.keys().copied().collect();
+        let hash = PerfectHash::build(&keys);
+        let max_codepoint = keys.last().copied().unwrap_or(0) as usize;
+        let unicode_width = address_width(max_codepoint + 1).max(1);
+        let words = self.glyph_words(opts);
+        let output_width = words.first().map_or(0, Vec::len);
+        let bits = |value: usize, width: usize| {
+            (0..width)
+                .rev()
+                .map(move |bit| value >> bit & 1 != 0)
+                .collect::<Vec<bool>>()
+        };
+        // Empty slots hold code point 0 and a blank glyph, so U+0000 without a glyph is blank too
+        let mut empty = vec![false; unicode_width];
+        empty.extend(blank_word(output_width, opts));
+        let mut entries = vec![empty; 1 << hash.slot_bits];
+        for (&key, &index) in &glyphs {
+            let entry = &mut entries[hash.slot(key)];
+            entry.clear();
+            entry.extend(bits(key as usize, unicode_width));
+            entry.extend_from_slice(&words[index]);
+        }
+        let displacements: Vec<Vec<bool>> = hash
+            .displacements
+            .iter()
+            .map(|&displacement| bits(displacement, hash.slot_bits as usize))
+            .collect();
+        write_rom_module(
+            opts,
+            out,
+            "glyphhash_displacement",
+            "bucket",
+            "displacement",
+            &displacements,
+            &[],
+        )?;
+        writeln!(out)?;
+        write_rom_module(opts, out, "glyphhash_rom", "slot", "entry", &entries, &[])?;
+        writeln!(out)?;
+        let (slot_msb, bucket_msb) = (hash.slot_bits - 1, hash.bucket_bits - 1);
+        writeln!(
+            out,
+            "module glyphhash ( input wire [{}:0] unicode, output wire [{}:0] glyph );",
+            unicode_width - 1,
+            output_width - 1
+        )?;
+        writeln!(
+            out,
+            "// a perfect hash of {} code points into {} slots: no two share a slot",
+            keys.len(),
+            entries.len()
+        )?;
+        writeln!(
+            out,
+            "wire [31:0] slot_product = unicode * 32'h{:08X};",
+            hash.slot_multiplier
+        )?;
+        writeln!(
+            out,
+            "wire [31:0] bucket_product = unicode * 32'h{:08X};",
+            hash.bucket_multiplier
+        )?;
+        writeln!(
+            out,
+            "wire [{}:0] bucket = bucket_product[31:{}];",
+            bucket_msb,
+            32 - hash.bucket_bits
+        )?;
+        writeln!(out, "wire [{}:0] displacement;", slot_msb)?;
+        writeln!(
+            out,
+            "glyphhash_displacement displacements ( .bucket(bucket), .displacement(displacement) );"
+        )?;
+        writeln!(
+            out,
+            "wire [{}:0] slot = slot_product[31:{}] ^ displacement;",
+            slot_msb,
+            32 - hash.slot_bits
+        )?;
+        writeln!(out, "wire [{}:0] entry;", unicode_width + output_width - 1)?;
+        writeln!(out, "glyphhash_rom rom ( .slot(slot), .entry(entry) );")?;
+        writeln!(
+            out,
+            "assign glyph = entry[{}:{}] == unicode ? entry[{}:0] : {};",
+            unicode_width + output_width - 1,
+            output_width,
+            output_width - 1,
+            blank_literal(output_width, opts)
+        )?;
+        writeln!(out, "endmodule")
+    }
+}
@@ -0,0 +1,130 @@
+use super::{ParseError, Version, PSF};
+use png::{BitDepth, ColorType, Decoder, Encoder, Transformations};
+use std::io::{self, BufRead, Seek, Write};
+
+impl PSF {
+    pub fn from_png<R: BufRead + Seek>(
+        reader: R,
+        cell_width: u32,
+        cell_height: u32,
+    ) -> Result<Self, ParseError> {
+        let mut decoder = Decoder::new(reader);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buf = vec![0u8; reader.output_buffer_size().unwrap_or(0)];
+        let info = reader.next_frame(&mut buf)?;
+        if cell_width == 0
+            || cell_height == 0
+            || info.width % cell_width != 0
+            || info.height % cell_height != 0
+        {
+            return Err(ParseError::BadImageDimensions(info.width, info.height));
+        }
+        let channels = info.color_type.samples();
+        let inked = |x: u32, y: u32| {
+            let pixel = &buf[y as usize * info.line_size + x as usize * channels..][..channels];
+            let (luma, alpha) = match info.color_type {
+                ColorType::Grayscale => (pixel[0] as u32, 255),
+                ColorType::GrayscaleAlpha => (pixel[0] as u32, pixel[1] as u32),
+                ColorType::Rgb | ColorType::Indexed => (
+                    (pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32) / 3,
+                    255,
+                ),
+                ColorType::Rgba => (
+                    (pixel[0] as u32 + pixel[1] as u32 + pixel[2] as u32) / 3,
+                    pixel[3] as u32,
+                ),
+            };
+            // Composite over white, so transparent pixels count as background
+            luma * alpha / 255 + (255 - alpha) < 128
+        };
+
+        let columns = info.width / cell_width;
+        let rows = info.height / cell_height;
+        let stride = (cell_width as usize).div_ceil(8);
+        let charsize = stride * cell_height as usize;
+        let mut bitmap = vec![0u8; charsize * (columns * rows) as usize];
+        for glyph in 0..(columns * rows) {
+            let (left, top) = (
+                (glyph % columns) * cell_width,
+                (glyph / columns) * cell_height,
+            );
+            for row in 0..cell_height {
+                for col in 0..cell_width {
+                    if inked(left + col, top + row) {
+                        bitmap[glyph as usize * charsize
+                            + row as usize * stride
+                            + col as usize / 8] |= 0x80 >> (col % 8);
+                    }
+                }
+            }
+        }
+        Ok(PSF {
+            version: Version::PSF2,
+            glyph_count: columns * rows,
+            charsize: charsize as u32,
+            height: cell_height,
+            width: cell_width,
+            bitmap,
+            table: None,
+            has_sequences: false,
+        })
+    }
+
+    /// Writes every glyph as black ink on white into a PNG atlas `columns` glyphs wide. At
+    /// `scale` 1 it is 1 bit per pixel; below 1 each output pixel is the average of the
+    /// source pixels it covers, as 8-bit gray.
+    pub fn write_preview_png<W: Write>(
+        &self,
+        columns: usize,
+        scale: f64,
+        out: W,
+    ) -> io::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let rows = (self.glyph_count as usize).div_ceil(columns);
+        let (atlas_width, atlas_height) = (columns * width, rows * height);
+        let inked = |x: usize, y: usize| {
+            let glyph = (y / height) * columns + x / width;
+            glyph < self.glyph_count as usize && self.glyph(glyph).pixel(y % height, x % width)
+        };
+        let scaled = |size: usize| ((size as f64 * scale).ceil() as usize).max(1);
+        let (out_width, out_height) = (scaled(atlas_width), scaled(atlas_height));
+        let mut encoder = Encoder::new(out, out_width as u32, out_height as u32);
+        encoder.set_color(ColorType::Grayscale);
+        let mut data = vec![];
+        if scale >= 1.0 {
+            encoder.set_depth(BitDepth::One);
+            let stride = atlas_width.div_ceil(8);
+            data.resize(stride * atlas_height, 0xFF);
+            for y in 0..atlas_height {
+                for x in 0..atlas_width {
+                    if inked(x, y) {
+                        data[y * stride + x / 8] &= !(0x80 >> (x % 8));
+                    }
+                }
+            }
+        } else {
+            encoder.set_depth(BitDepth::Eight);
+            // The source pixels [start, end) that output pixel `i` covers
+            let span = |i: usize, size: usize| {
+                let start = ((i as f64 / scale) as usize).min(size - 1);
+                let end = (((i + 1) as f64 / scale) as usize).clamp(start + 1, size);
+                start..end
+            };
+            for oy in 0..out_height {
+                for ox in 0..out_width {
+                    let (xs, ys) = (span(ox, atlas_width), span(oy, atlas_height));
+                    let total = xs.len() * ys.len();
+                    let set = ys
+                        .flat_map(|y| xs.clone().map(move |x| (x, y)))
+                        .filter(|&(x, y)| inked(x, y))
+                        .count();
+                    data.push((255 - 255 * set / total) as u8);
+                }
+            }
+        }
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        Ok(writer.finish()?)
+    }
+}
@@ -0,0 +1,135 @@
+use super::{ParseError, TableEntry, Version, PSF};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+#[derive(Serialize, Deserialize)]
+struct Font {
+    version: String,
+    width: u32,
+    height: u32,
+    /// Each glyph's bytes in hex, rows top to bottom as the font stores them
+    glyphs: Vec<String>,
+    table: Option<Vec<Entry>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    represented: Vec<String>,
+    sequences: Vec<String>,
+}
+
+fn codepoints(chars: &[char]) -> Vec<String> {
+    chars
+        .iter()
+        .map(|&c| format!("U+{:04X}", c as u32))
+        .collect()
+}
+
+fn chars(codepoints: &[String]) -> Result<Vec<char>, ParseError> {
+    codepoints
+        .iter()
+        .map(|codepoint| {
+            codepoint
+                .strip_prefix("U+")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(char::from_u32)
+                .ok_or_else(|| ParseError::MalformedJSON(format!("bad code point {}", codepoint)))
+        })
+        .collect()
+}
+
+fn bytes(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+impl PSF {
+    pub(crate) fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let font = Font {
+            version: self.version.to_string(),
+            width: self.width,
+            height: self.height,
+            glyphs: self
+                .bitmap
+                .chunks_exact(self.charsize as usize)
+                .take(self.glyph_count as usize)
+                .map(|glyph| glyph.iter().map(|byte| format!("{:02X}", byte)).collect())
+                .collect(),
+            table: self.table.as_ref().map(|table| {
+                table
+                    .iter()
+                    .map(|entry| Entry {
+                        represented: codepoints(&entry.represented),
+                        sequences: codepoints(&entry.sequences),
+                    })
+                    .collect()
+            }),
+        };
+        serde_json::to_writer_pretty(&mut *out, &font)?;
+        writeln!(out)
+    }
+
+    /// Reads a font written by `--format json`.
+    pub fn from_json<R: Read>(reader: R) -> Result<Self, ParseError> {
+        let font: Font = serde_json::from_reader(reader)
+            .map_err(|err| ParseError::MalformedJSON(err.to_string()))?;
+        let version = match font.version.as_str() {
+            "PSF1" => Version::PSF1,
+            "PSF2" => Version::PSF2,
+            "BDF" => Version::Bdf,
+            other => return Err(ParseError::MalformedJSON(format!("bad version {}", other))),
+        };
+        let needed = (font.width as u64).div_ceil(8) * font.height as u64;
+        let charsize = font
+            .glyphs
+            .first()
+            .map_or(needed, |glyph| glyph.len() as u64 / 2)
+            .max(needed);
+        let charsize = u32::try_from(charsize).map_err(|_| {
+            ParseError::BadGeometry(format!(
+                "{}x{} glyphs need {} bytes each",
+                font.width, font.height, charsize
+            ))
+        })?;
+        Self::checked_geometry(font.width, font.height, charsize)?;
+        let mut bitmap = vec![];
+        for glyph in &font.glyphs {
+            match bytes(glyph) {
+                Some(glyph) if glyph.len() == charsize as usize => bitmap.extend(glyph),
+                _ => {
+                    return Err(ParseError::MalformedJSON(format!(
+                        "glyph {} is not {} hex bytes",
+                        glyph, charsize
+                    )))
+                }
+            }
+        }
+        let table = match font.table {
+            Some(entries) => Some(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        Ok(TableEntry {
+                            represented: chars(&entry.represented)?,
+                            sequences: chars(&entry.sequences)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, ParseError>>()?,
+            ),
+            None => None,
+        };
+        Ok(PSF {
+            version,
+            glyph_count: font.glyphs.len() as u32,
+            charsize,
+            height: font.height,
+            width: font.width,
+            bitmap,
+            has_sequences: Self::sequences_in(&table),
+            table,
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use super::{FontGeometry, ParseError, PSF};
+use std::io::{Read, Seek, SeekFrom};
+
+/// A font whose glyphs are read from `reader` one at a time, for fonts too large to be worth
+/// loading to look at a few glyphs. Only the header is parsed up front: each `glyph_bytes`
+/// call costs a seek and a read, where `PSF` pays for the whole bitmap once and then reads
+/// from memory. The unicode table is never read.
+#[derive(Debug)]
+pub struct LazyPSF<R> {
+    reader: R,
+    bitmap_offset: u64,
+    geometry: FontGeometry,
+}
+
+impl PSF {
+    /// Parses the header of a PSF1 or PSF2 font and leaves the bitmap in `reader`, to be
+    /// fetched glyph by glyph. The remaining length of the reader is checked against the
+    /// header so that a truncated font fails here rather than on some later glyph.
+    pub fn open_lazy<R: Read + Seek>(mut reader: R) -> Result<LazyPSF<R>, ParseError> {
+        let mut magic = [0u8; 4];
+        Self::read_header(&mut reader, &mut magic)?;
+        let geometry = if magic[0..2] == Self::PSF1_MAGIC {
+            Self::read_psf1_header(&magic, &mut vec![])?.0
+        } else if magic == Self::PSF2_MAGIC {
+            Self::read_psf2_header(&mut reader, &mut vec![])?.0
+        } else {
+            return Err(Self::sniff_other_format(&magic));
+        };
+        let bitmap_offset = reader.stream_position()?;
+        let bitmap_len = geometry.charsize as u64 * geometry.glyph_count as u64;
+        if Self::remaining(&mut reader)? < bitmap_len {
+            return Err(ParseError::IoError(
+                std::io::ErrorKind::UnexpectedEof.into(),
+            ));
+        }
+        Ok(LazyPSF {
+            reader,
+            bitmap_offset,
+            geometry,
+        })
+    }
+}
+
+impl<R: Read + Seek> LazyPSF<R> {
+    pub fn geometry(&self) -> FontGeometry {
+        self.geometry
+    }
+
+    /// Reads the `charsize` bytes of glyph `index`, or None past the last glyph.
+    pub fn glyph_bytes(&mut self, index: u32) -> Result<Option<Vec<u8>>, ParseError> {
+        if index >= self.geometry.glyph_count {
+            return Ok(None);
+        }
+        let charsize = self.geometry.charsize as u64;
+        self.reader.seek(SeekFrom::Start(
+            self.bitmap_offset + index as u64 * charsize,
+        ))?;
+        let mut bytes = vec![0u8; charsize as usize];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+}
@@ -0,0 +1,870 @@
+mod backend;
+mod bdf;
+mod check;
+mod codepage;
+mod diff;
+mod find;
+mod glyph;
+mod hash;
+mod image;
+#[cfg(feature = "json")]
+mod json;
+mod lazy;
+#[cfg(feature = "net")]
+pub mod net;
+mod notes;
+mod options;
+mod pbm;
+mod preview;
+mod progress;
+mod python;
+mod report;
+mod rle;
+mod roundtrip;
+mod scala;
+mod selftest;
+mod stats;
+mod transform;
+mod unimap;
+mod verilog;
+mod vhdl;
+
+pub use backend::{backend, format_names, langs, named, Backend, BACKENDS};
+pub use codepage::Codepage;
+pub use glyph::{Bounds, Glyph};
+pub use lazy::LazyPSF;
+pub use notes::{collect_notes, Note};
+pub use options::{
+    AssignStyle, ClockEdge, Command, Compression, Endian, FoldCase, Format, Lang, Options, Parity,
+    Psf1Pages, Radix, Target,
+};
+pub use pbm::parse_pbm;
+pub use progress::Progress;
+pub use rle::{rle_decode, rle_encode};
+pub use transform::{parse_codepage_map, subset_codepoints};
+
+use log::debug;
+use std::char::decode_utf16;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::iter::Iterator;
+
+#[derive(Debug)]
+pub enum ParseError {
+    IoError(std::io::Error),
+    NotPSF,
+    /// The file, of this many bytes, ends inside the PSF header
+    TooShort(u64),
+    OtherFontFormat(&'static str),
+    ByteSwapped(Version),
+    MalformedBDF(String),
+    MalformedJSON(String),
+    MalformedPBM(String),
+    /// A unicode table entry, by index, whose code points do not decode
+    MalformedTable(usize, String),
+    BadGeometry(String),
+    Http(String),
+    Png(png::DecodingError),
+    BadImageDimensions(u32, u32),
+    UnsupportedVersion,
+    InvalidArgument(String),
+    MissingValue(String),
+}
+
+impl From<std::io::Error> for ParseError {
+    fn from(err: std::io::Error) -> Self {
+        Self::IoError(err)
+    }
+}
+
+impl From<png::DecodingError> for ParseError {
+    fn from(err: png::DecodingError) -> Self {
+        Self::Png(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    ShortHeader(u32),
+    UnknownFlags(u32),
+    TruncatedTable(usize),
+    EmptyTable,
+    TrailingData(usize),
+    /// A lone NUL or newline after the font, only reported by `--check`
+    StrayByte(u8),
+    PaddedGlyphs(u32, u32),
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ShortHeader(size) => write!(f, "header_size should be >= 32 but = {}", size),
+            Self::UnknownFlags(flags) => write!(f, "ignoring unknown header flags {:#x}", flags),
+            Self::TruncatedTable(entries) => {
+                write!(f, "unicode table ends early, after {} entries", entries)
+            }
+            Self::EmptyTable => write!(f, "unicode table flag is set but the table is empty"),
+            Self::TrailingData(bytes) => write!(f, "ignoring {} bytes after the font", bytes),
+            Self::StrayByte(byte) => {
+                write!(f, "ignoring a stray {:#04x} byte after the font", byte)
+            }
+            Self::PaddedGlyphs(charsize, needed) => write!(
+                f,
+                "ignoring {} pad bytes after the {} pixel bytes of each {}-byte glyph",
+                charsize - needed,
+                needed,
+                charsize
+            ),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TableEntry {
+    /// Code points drawn with the glyph on their own; empty when the entry starts with a
+    /// sequence
+    pub represented: Vec<char>,
+    /// The code points of every sequence in the entry, one after another
+    pub sequences: Vec<char>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    PSF1,
+    PSF2,
+    Bdf,
+}
+
+impl Version {
+    /// The PSF major version, or None for a font read from BDF.
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Self::PSF1 => Some(1),
+            Self::PSF2 => Some(2),
+            Self::Bdf => None,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::PSF1 => "PSF1",
+            Self::PSF2 => "PSF2",
+            Self::Bdf => "BDF",
+        })
+    }
+}
+
+/// The format and dimensions of a parsed font.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FontGeometry {
+    pub version: Version,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes per glyph, which may include padding beyond the rows of pixels
+    pub charsize: u32,
+    pub glyph_count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct PSF {
+    version: Version,
+    glyph_count: u32,
+    charsize: u32,
+    height: u32,
+    width: u32,
+    bitmap: Vec<u8>,
+    table: Option<Vec<TableEntry>>,
+    has_sequences: bool,
+}
+
+impl PSF {
+    const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+    const PSF1_MODE512: u8 = 0x01;
+    const PSF1_MODEHASTAB: u8 = 0x02;
+    const PSF1_MODEHASSEQ: u8 = 0x04;
+    #[allow(dead_code)]
+    const PSF1_MAXMODE: u8 = 0x05;
+    const PSF1_SEPARATOR: u16 = 0xFFFF;
+    const PSF1_STARTSEQ: u16 = 0xFFFE;
+    /// PSF1 glyphs are always one byte wide, so each glyph is `height` bytes
+    const PSF1_WIDTH: u32 = 8;
+
+    const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+    const PSF2_SEPARATOR: u8 = 0xFF;
+    const PSF2_STARTSEQ: u8 = 0xFE;
+    const PSF2_MAXVERSION: u32 = 0;
+    const PSF2_HASUNICODETABLE: u32 = 0x01;
+
+    /// The bytes of pixels in each `width`x`height` glyph, failing when the glyphs have no
+    /// pixels or `charsize` is too small to hold them.
+    pub(crate) fn checked_geometry(
+        width: u32,
+        height: u32,
+        charsize: u32,
+    ) -> Result<u32, ParseError> {
+        if width == 0 || height == 0 {
+            return Err(ParseError::BadGeometry(format!(
+                "{}x{} glyphs have no pixels",
+                width, height
+            )));
+        }
+        let needed = width.div_ceil(8) as u64 * height as u64;
+        if (charsize as u64) < needed {
+            return Err(ParseError::BadGeometry(format!(
+                "{} bytes per glyph, {}x{} needs {}",
+                charsize, width, height, needed
+            )));
+        }
+        Ok(needed as u32)
+    }
+
+    pub(crate) fn sequences_in(table: &Option<Vec<TableEntry>>) -> bool {
+        table
+            .iter()
+            .flatten()
+            .any(|entry| !entry.sequences.is_empty())
+    }
+
+    /// Adds a run of code points to the entry, as the code points it represents or, after
+    /// the first sequence start, to its sequences.
+    fn push_run<I: Iterator<Item = char>>(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        chars: I,
+    ) {
+        if !sequence_started {
+            entry.represented.extend(chars);
+        } else {
+            entry.sequences.extend(chars);
+        }
+    }
+
+    /// Reads entries until `glyph_count` are complete or the table ends, returning them with
+    /// the bytes used; `S` decides how each run of code points is stored.
+    fn parse_table<S: RunSink>(
+        table: &[u8],
+        version: &Version,
+        glyph_count: usize,
+        psf1_stride: usize,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(Vec<TableEntry>, usize), ParseError> {
+        let mut entries = S::entries(table, version, glyph_count, psf1_stride);
+        let mut current_entry = TableEntry::default();
+        let mut sequence_started = false;
+        match version {
+            Version::PSF1 => {
+                let mut codepoints = vec![];
+                // Nonstandard fonts may pad each code point out to psf1_stride bytes
+                for (index, pair) in table.chunks_exact(psf1_stride).enumerate() {
+                    let codepoint = u16::from_le_bytes(pair[..2].try_into().unwrap());
+                    if codepoint == Self::PSF1_SEPARATOR || codepoint == Self::PSF1_STARTSEQ {
+                        S::store_utf16(
+                            &mut current_entry,
+                            sequence_started,
+                            &mut codepoints,
+                            entries.len(),
+                        )?;
+
+                        if codepoint == Self::PSF1_SEPARATOR {
+                            sequence_started = false;
+                            entries.push(std::mem::take(&mut current_entry));
+                            if entries.len() == glyph_count {
+                                return Ok((entries, (index + 1) * psf1_stride));
+                            }
+                        } else {
+                            sequence_started = true;
+                        }
+                    } else {
+                        codepoints.push(codepoint);
+                    }
+                }
+                // A truncated table can end mid-entry; keep what was read of it
+                if sequence_started || !codepoints.is_empty() {
+                    S::store_utf16(
+                        &mut current_entry,
+                        sequence_started,
+                        &mut codepoints,
+                        entries.len(),
+                    )?;
+                    entries.push(current_entry);
+                }
+            }
+            Version::PSF2 => {
+                let mut codepoints = vec![];
+                for (index, &codepoint) in table.iter().enumerate() {
+                    if codepoint == Self::PSF2_SEPARATOR || codepoint == Self::PSF2_STARTSEQ {
+                        S::store_utf8(
+                            &mut current_entry,
+                            sequence_started,
+                            &mut codepoints,
+                            entries.len(),
+                        )?;
+
+                        if codepoint == Self::PSF2_SEPARATOR {
+                            sequence_started = false;
+                            entries.push(std::mem::take(&mut current_entry));
+                            if entries.len() == glyph_count {
+                                return Ok((entries, index + 1));
+                            }
+                        } else {
+                            sequence_started = true;
+                        }
+                    } else {
+                        codepoints.push(codepoint);
+                    }
+                }
+                if sequence_started || !codepoints.is_empty() {
+                    S::store_utf8(
+                        &mut current_entry,
+                        sequence_started,
+                        &mut codepoints,
+                        entries.len(),
+                    )?;
+                    entries.push(current_entry);
+                }
+            }
+            Version::Bdf => unreachable!("BDF fonts have no binary unicode table"),
+        }
+        debug!(
+            "unicode table ends after {} of {} entries",
+            entries.len(),
+            glyph_count
+        );
+        warnings.push(Warning::TruncatedTable(entries.len()));
+        Ok((entries, table.len()))
+    }
+
+    /// Fails on a PSF1 run of code points that is not valid UTF-16, naming its entry.
+    fn check_utf16(codepoints: &[u16], entry: usize) -> Result<(), ParseError> {
+        match decode_utf16(codepoints.iter().copied()).find_map(Result::err) {
+            Some(err) => Err(ParseError::MalformedTable(entry, err.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    fn utf8_run(codepoints: &[u8], entry: usize) -> Result<&str, ParseError> {
+        std::str::from_utf8(codepoints)
+            .map_err(|err| ParseError::MalformedTable(entry, err.to_string()))
+    }
+
+    fn sniff_other_format(magic: &[u8; 4]) -> ParseError {
+        match magic {
+            [0x00, 0x01, 0x00, 0x00] | b"true" => ParseError::OtherFontFormat("TrueType"),
+            b"OTTO" => ParseError::OtherFontFormat("OpenType"),
+            b"wOFF" => ParseError::OtherFontFormat("WOFF"),
+            b"wOF2" => ParseError::OtherFontFormat("WOFF2"),
+            [0x86, 0x4a, 0xb5, 0x72] | [0xb5, 0x72, 0x86, 0x4a] => {
+                ParseError::ByteSwapped(Version::PSF2)
+            }
+            [0x04, 0x36, _, _] => ParseError::ByteSwapped(Version::PSF1),
+            _ => ParseError::NotPSF,
+        }
+    }
+
+    /// Reads part of the header, reporting a file that ends before it as too short to be a
+    /// font rather than as a bare end-of-file error.
+    fn read_header<R: Read + Seek>(psf_file: &mut R, header: &mut [u8]) -> Result<(), ParseError> {
+        match psf_file.read_exact(header) {
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Err(ParseError::TooShort(psf_file.seek(SeekFrom::End(0))?))
+            }
+            result => Ok(result?),
+        }
+    }
+
+    pub fn add_identity_table(&mut self) {
+        if self.table.is_none() {
+            let table = (0..self.glyph_count)
+                .map(|index| TableEntry {
+                    represented: char::from_u32(index).into_iter().collect(),
+                    sequences: vec![],
+                })
+                .collect();
+            self.table = Some(table);
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    pub fn glyph_count(&self) -> u32 {
+        self.glyph_count
+    }
+
+    /// Whether the unicode table may hold sequences: the PSF1 mode flag, or for other fonts
+    /// whether any table entry has one.
+    pub fn has_sequences(&self) -> bool {
+        self.has_sequences
+    }
+
+    pub fn geometry(&self) -> FontGeometry {
+        FontGeometry {
+            version: self.version,
+            width: self.width,
+            height: self.height,
+            charsize: self.charsize,
+            glyph_count: self.glyph_count,
+        }
+    }
+
+    /// The bytes of each glyph holding pixels; fonts may pad `charsize` beyond this.
+    pub(crate) fn pixel_bytes(&self) -> usize {
+        (self.width.div_ceil(8) * self.height).min(self.charsize) as usize
+    }
+
+    /// The `charsize` bytes of glyph `index`, or None past the last glyph held in the bitmap.
+    pub fn glyph_bytes(&self, index: u32) -> Option<&[u8]> {
+        if index >= self.glyph_count {
+            return None;
+        }
+        let charsize = self.charsize as usize;
+        let start = (index as usize).checked_mul(charsize)?;
+        self.bitmap.get(start..start.checked_add(charsize)?)
+    }
+
+    pub fn glyph(&self, index: usize) -> Glyph<'_> {
+        let bytes = self
+            .glyph_bytes(index as u32)
+            .unwrap_or_else(|| panic!("glyph {} out of range", index));
+        Glyph::new(bytes, self.width as usize, self.height as usize)
+    }
+
+    pub(crate) fn set_pixels(&self, glyph: usize) -> usize {
+        let glyph = self.glyph(glyph);
+        (0..glyph.height())
+            .map(|row| glyph.row_bits(row).filter(|&set| set).count())
+            .sum()
+    }
+}
+
+/// How `PSF::parse_table` stores each run of code points in its entry.
+trait RunSink {
+    /// The empty entry list to fill.
+    fn entries(
+        table: &[u8],
+        version: &Version,
+        glyph_count: usize,
+        psf1_stride: usize,
+    ) -> Vec<TableEntry>;
+
+    /// Stores a PSF1 run, leaving `codepoints` empty.
+    fn store_utf16(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u16>,
+        index: usize,
+    ) -> Result<(), ParseError>;
+
+    /// Stores a PSF2 run, leaving `codepoints` empty.
+    fn store_utf8(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u8>,
+        index: usize,
+    ) -> Result<(), ParseError>;
+}
+
+/// Decodes each run into a new vector of its own.
+struct SimpleRuns;
+
+impl RunSink for SimpleRuns {
+    fn entries(_: &[u8], _: &Version, _: usize, _: usize) -> Vec<TableEntry> {
+        vec![]
+    }
+
+    fn store_utf16(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u16>,
+        index: usize,
+    ) -> Result<(), ParseError> {
+        let chars = decode_utf16(std::mem::take(codepoints))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| ParseError::MalformedTable(index, err.to_string()))?;
+        PSF::push_run(entry, sequence_started, chars.into_iter());
+        Ok(())
+    }
+
+    fn store_utf8(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u8>,
+        index: usize,
+    ) -> Result<(), ParseError> {
+        let chars: Vec<char> = PSF::utf8_run(codepoints, index)?.chars().collect();
+        PSF::push_run(entry, sequence_started, chars.into_iter());
+        *codepoints = vec![];
+        Ok(())
+    }
+}
+
+/// `--compact-table`: counts the entries first so the list is allocated once, and decodes
+/// every run through one reused buffer straight into its entry.
+struct CompactRuns;
+
+impl RunSink for CompactRuns {
+    fn entries(
+        table: &[u8],
+        version: &Version,
+        glyph_count: usize,
+        psf1_stride: usize,
+    ) -> Vec<TableEntry> {
+        let separators = match version {
+            Version::PSF1 => table
+                .chunks_exact(psf1_stride)
+                .filter(|pair| pair[..2] == PSF::PSF1_SEPARATOR.to_le_bytes())
+                .count(),
+            _ => table
+                .iter()
+                .filter(|&&byte| byte == PSF::PSF2_SEPARATOR)
+                .count(),
+        };
+        Vec::with_capacity(glyph_count.min(separators + 1))
+    }
+
+    fn store_utf16(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u16>,
+        index: usize,
+    ) -> Result<(), ParseError> {
+        PSF::check_utf16(codepoints, index)?;
+        PSF::push_run(
+            entry,
+            sequence_started,
+            decode_utf16(codepoints.drain(..)).flatten(),
+        );
+        Ok(())
+    }
+
+    fn store_utf8(
+        entry: &mut TableEntry,
+        sequence_started: bool,
+        codepoints: &mut Vec<u8>,
+        index: usize,
+    ) -> Result<(), ParseError> {
+        PSF::push_run(
+            entry,
+            sequence_started,
+            PSF::utf8_run(codepoints, index)?.chars(),
+        );
+        codepoints.clear();
+        Ok(())
+    }
+}
+
+impl TryFrom<File> for PSF {
+    type Error = ParseError;
+    fn try_from(psf_file: File) -> Result<Self, Self::Error> {
+        Self::parse_reader(psf_file)
+    }
+}
+
+impl PSF {
+    /// Reads everything after the bitmap: the binary unicode table when the header declares
+    /// one, then any kbd text unimap appended to the font. The unimap only supplies the table
+    /// of a font without one; other trailing data is ignored. A single NUL or newline left at
+    /// the end by a text-mode transfer is tolerated, even after a truncated table.
+    fn read_table<R: Read>(
+        psf_file: &mut R,
+        version: &Version,
+        has_table: bool,
+        glyph_count: usize,
+        psf1_stride: usize,
+        compact: bool,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Option<Vec<TableEntry>>, ParseError> {
+        let mut rest = vec![];
+        psf_file.read_to_end(&mut rest)?;
+        debug!("{} bytes follow the bitmap", rest.len());
+        let stray = rest
+            .last()
+            .copied()
+            .filter(|&byte| byte == 0x00 || byte == b'\n');
+        let empty = rest.len() == stray.is_some() as usize;
+        if has_table && empty {
+            warnings.push(Warning::EmptyTable);
+        }
+        let (table, consumed) = if has_table && !empty {
+            let parse_table = if compact {
+                Self::parse_table::<CompactRuns>
+            } else {
+                Self::parse_table::<SimpleRuns>
+            };
+            let mut table_warnings = vec![];
+            let (mut table, mut consumed) = parse_table(
+                &rest,
+                version,
+                glyph_count,
+                psf1_stride,
+                &mut table_warnings,
+            )?;
+            // Read as part of a truncated table, the stray byte would become a code point. A
+            // PSF1 table only has one when it leaves a partial code point, as the high byte
+            // of every code point below U+0100 is zero.
+            let partial = *version != Version::PSF1 || rest.len() % psf1_stride != 0;
+            if table.len() < glyph_count && consumed == rest.len() && stray.is_some() && partial {
+                debug!("rereading the table without its last byte");
+                table_warnings.clear();
+                table = parse_table(
+                    &rest[..rest.len() - 1],
+                    version,
+                    glyph_count,
+                    psf1_stride,
+                    &mut table_warnings,
+                )?
+                .0;
+                consumed = rest.len() - 1;
+            }
+            warnings.append(&mut table_warnings);
+            debug!(
+                "parsed {} table entries from {} bytes",
+                table.len(),
+                consumed
+            );
+            (Some(table), consumed)
+        } else {
+            (None, 0)
+        };
+        let unimap = std::str::from_utf8(&rest[consumed..])
+            .ok()
+            .and_then(|text| unimap::parse(text, glyph_count));
+        if let Some(unimap) = &unimap {
+            debug!("read a text unimap with {} entries", unimap.len());
+        }
+        match stray {
+            Some(byte) if unimap.is_none() && consumed + 1 == rest.len() => {
+                warnings.push(Warning::StrayByte(byte))
+            }
+            _ if unimap.is_none() && consumed < rest.len() => {
+                warnings.push(Warning::TrailingData(rest.len() - consumed))
+            }
+            _ => {}
+        }
+        Ok(table.or(unimap))
+    }
+
+    /// Reads the geometry and mode byte from the `magic` of a PSF1 font, which holds its
+    /// whole header.
+    pub(crate) fn read_psf1_header(
+        magic: &[u8; 4],
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(FontGeometry, u8), ParseError> {
+        let mode = magic[2];
+        debug!("PSF1 font, mode {:#04x}, height {}", mode, magic[3]);
+        let unknown_mode =
+            mode & !(Self::PSF1_MODE512 | Self::PSF1_MODEHASTAB | Self::PSF1_MODEHASSEQ);
+        if unknown_mode != 0 {
+            warnings.push(Warning::UnknownFlags(unknown_mode as u32));
+        }
+        let height = magic[3] as u32;
+        // Every row is one byte, so a glyph is as many bytes as rows
+        debug_assert_eq!(Self::PSF1_WIDTH.div_ceil(8), 1);
+        let geometry = FontGeometry {
+            version: Version::PSF1,
+            width: Self::PSF1_WIDTH,
+            height,
+            charsize: Self::checked_geometry(Self::PSF1_WIDTH, height, height)?,
+            glyph_count: if mode & Self::PSF1_MODE512 != 0 {
+                512
+            } else {
+                256
+            },
+        };
+        Ok((geometry, mode))
+    }
+
+    /// Reads the rest of a PSF2 header after its magic, leaving `reader` at the bitmap, for
+    /// the geometry and flags.
+    pub(crate) fn read_psf2_header<R: Read + Seek>(
+        reader: &mut R,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(FontGeometry, u32), ParseError> {
+        let mut header = [0u8; 7 * 4];
+        Self::read_header(reader, &mut header)?;
+        let field = |n: usize| u32::from_le_bytes(header[n * 4..][..4].try_into().unwrap());
+        let (header_version, header_size, flags) = (field(0), field(1), field(2));
+        let geometry = FontGeometry {
+            version: Version::PSF2,
+            width: field(6),
+            height: field(5),
+            charsize: field(4),
+            glyph_count: field(3),
+        };
+        debug!(
+            "PSF2 font, version {}, header size {}, flags {:#x}, {} glyphs of {} bytes, {}x{}",
+            header_version,
+            header_size,
+            flags,
+            geometry.glyph_count,
+            geometry.charsize,
+            geometry.width,
+            geometry.height
+        );
+        if header_version > Self::PSF2_MAXVERSION {
+            return Err(ParseError::UnsupportedVersion);
+        }
+        if flags & !Self::PSF2_HASUNICODETABLE != 0 {
+            warnings.push(Warning::UnknownFlags(flags & !Self::PSF2_HASUNICODETABLE));
+        }
+        let needed = Self::checked_geometry(geometry.width, geometry.height, geometry.charsize)?;
+        if geometry.charsize > needed {
+            warnings.push(Warning::PaddedGlyphs(geometry.charsize, needed));
+        }
+        if header_size >= 32 {
+            // Skip the remainder of the header
+            reader.seek(SeekFrom::Current((header_size - 32) as i64))?;
+        } else {
+            warnings.push(Warning::ShortHeader(header_size));
+        }
+        Ok((geometry, flags))
+    }
+
+    /// The bytes left in `reader` after its position, which is kept.
+    pub(crate) fn remaining<R: Seek>(reader: &mut R) -> Result<u64, ParseError> {
+        let position = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(position))?;
+        end.checked_sub(position).ok_or(ParseError::TooShort(end))
+    }
+
+    /// Logs the anomalies recovered from while parsing a font.
+    fn trace_warnings(warnings: &[Warning]) {
+        for warning in warnings {
+            debug!("recovered: {}", warning);
+        }
+    }
+
+    pub fn parse_reader<R: Read + Seek>(psf_file: R) -> Result<Self, ParseError> {
+        Self::parse_reader_with_warnings(psf_file, &Options::default()).map(|(psf, _)| psf)
+    }
+
+    pub fn parse_reader_with_warnings<R: Read + Seek>(
+        mut psf_file: R,
+        opts: &Options,
+    ) -> Result<(Self, Vec<Warning>), ParseError> {
+        let mut warnings = vec![];
+        let mut magic = [0u8; 4];
+        Self::read_header(&mut psf_file, &mut magic)?;
+        debug!("magic {:02x?}", magic);
+        if magic[0..2] == Self::PSF1_MAGIC {
+            let version = Version::PSF1;
+            let (geometry, mode) = Self::read_psf1_header(&magic, &mut warnings)?;
+            let length = opts.psf1_count.unwrap_or(geometry.glyph_count as usize);
+            let charsize = geometry.charsize as usize;
+            if opts.psf1_count.is_some() {
+                let available = Self::remaining(&mut psf_file)?;
+                if (charsize * length) as u64 > available {
+                    return Err(ParseError::InvalidArgument(format!(
+                        "--psf1-count {} needs {} bitmap bytes but only {} remain",
+                        length,
+                        charsize * length,
+                        available
+                    )));
+                }
+            }
+            let mut bitmap = vec![0u8; charsize * length];
+            psf_file.read_exact(&mut bitmap)?;
+            debug!("read {} bitmap bytes for {} glyphs", bitmap.len(), length);
+            let table = if opts.strip_table {
+                None
+            } else {
+                Self::read_table(
+                    &mut psf_file,
+                    &version,
+                    mode & Self::PSF1_MODEHASTAB != 0,
+                    length,
+                    opts.psf1_table_stride.unwrap_or(2),
+                    opts.compact_table,
+                    &mut warnings,
+                )?
+            };
+            Self::trace_warnings(&warnings);
+            Ok((
+                PSF {
+                    version,
+                    glyph_count: length as u32,
+                    charsize: geometry.charsize,
+                    height: geometry.height,
+                    width: geometry.width,
+                    bitmap,
+                    table,
+                    has_sequences: mode & Self::PSF1_MODEHASSEQ != 0,
+                },
+                warnings,
+            ))
+        } else if magic == Self::PSF2_MAGIC {
+            let version = Version::PSF2;
+            let (geometry, flags) = Self::read_psf2_header(&mut psf_file, &mut warnings)?;
+            let (length, charsize) = (geometry.glyph_count, geometry.charsize);
+            let bitmap_len = charsize.checked_mul(length).ok_or_else(|| {
+                ParseError::BadGeometry(format!(
+                    "{} glyphs of {} bytes do not fit in a bitmap",
+                    length, charsize
+                ))
+            })?;
+            // Fail on a short file before allocating whatever size the header claims
+            if bitmap_len as u64 > Self::remaining(&mut psf_file)? {
+                return Err(ParseError::IoError(
+                    std::io::ErrorKind::UnexpectedEof.into(),
+                ));
+            }
+            let mut bitmap = vec![0u8; bitmap_len as usize];
+            psf_file.read_exact(&mut bitmap)?;
+            debug!("read {} bitmap bytes", bitmap.len());
+
+            let table = if opts.strip_table {
+                None
+            } else {
+                Self::read_table(
+                    &mut psf_file,
+                    &version,
+                    flags & Self::PSF2_HASUNICODETABLE > 0,
+                    length as usize,
+                    opts.psf1_table_stride.unwrap_or(2),
+                    opts.compact_table,
+                    &mut warnings,
+                )?
+            };
+
+            Self::trace_warnings(&warnings);
+            Ok((
+                PSF {
+                    version,
+                    glyph_count: length,
+                    charsize,
+                    height: geometry.height,
+                    width: geometry.width,
+                    bitmap,
+                    has_sequences: Self::sequences_in(&table),
+                    table,
+                },
+                warnings,
+            ))
+        } else if &magic == b"STAR" {
+            psf_file.seek(SeekFrom::Current(-4))?;
+            let mut text = vec![];
+            psf_file.read_to_end(&mut text)?;
+            let text = String::from_utf8_lossy(&text);
+            if text.starts_with("STARTFONT") {
+                debug!("BDF font, {} bytes of text", text.len());
+                bdf::parse(&text).map(|mut psf| {
+                    if opts.strip_table {
+                        psf.table = None;
+                    }
+                    (psf, warnings)
+                })
+            } else {
+                Err(ParseError::NotPSF)
+            }
+        } else {
+            Err(Self::sniff_other_format(&magic))
+        }
+    }
+}
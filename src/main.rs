@@ -1,239 +1,923 @@
-use std::char::decode_utf16;
-use std::convert::{TryFrom, TryInto};
+use psf2verilog::{
+    backend, collect_notes, format_names, langs, parse_codepage_map, parse_pbm, subset_codepoints,
+    Command, Note, Options, ParseError, Progress, Warning, PSF,
+};
 use std::env;
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
-use std::iter::Iterator;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::process;
 
-fn main() -> Result<(), ParseError> {
-    match env::args().skip(1).next() {
-        Some(path) => {
-            let psf_file = File::open(Path::new(&path))?;
-            PSF::try_from(psf_file)?.into_verilog();
-        }
-        None => {
-            eprintln!("Usage: psf2verilog <PSF_FONT_FILENAME>");
+const WRAP_PLACEHOLDER: &str = "{{FONT_MODULE}}";
+
+const USAGE: &str = "Usage: psf2verilog [OPTIONS] <PSF_FONT_FILENAME>
+       psf2verilog stats <PSF_FONT_FILENAME>
+       psf2verilog export-glyphs [--out-dir DIR] <PSF_FONT_FILENAME>
+       psf2verilog diff [--show-glyphs] <PSF_FONT_FILENAME> <PSF_FONT_FILENAME>
+       psf2verilog delta [--output FILE] [OPTIONS] <BASE_FONT> <NEW_FONT>
+       psf2verilog preview [--preview-cols N] [--preview-png FILE [--preview-scale S]]
+                           [--preview-svg FILE]
+                           <PSF_FONT_FILENAME>
+       psf2verilog find --pbm PATTERN [--tolerance N] <PSF_FONT_FILENAME>
+       psf2verilog query --char C <PSF_FONT_FILENAME>
+       psf2verilog selftest
+       psf2verilog --emit-selectable [OPTIONS] <PSF_FONT_FILENAME>...
+       psf2verilog combine-planes [OPTIONS] <PSF_FONT_FILENAME> <PSF_FONT_FILENAME>
+       psf2verilog import-png --cell-width W --cell-height H [OPTIONS] <PNG_FILENAME>
+       psf2verilog import-glyphs [--width W --height H] [OPTIONS] <DIR>
+
+The font may be a PSF1, PSF2 or BDF file, or with the net feature an http:// or https:// URL
+to download it from (redirects are followed and an error status fails). A PSF file may be
+followed by a kbd text unimap (lines like \"0x41 U+0041 U+0391\", \"0x80-0x9f U+0080-U+009f\"
+or \"0x20-0x7e idem\"), which is used as the unicode table when the font has none; other
+trailing data is ignored with a warning, except for a single NUL or newline byte left by a
+text-mode transfer, which is accepted silently (--check still reports it), even when it
+follows a truncated table.
+A PSF2 font whose charsize pads each glyph beyond its rows of pixels is read in full, with
+a warning, and the padding is left out of the glyph words (byte-port keeps it).
+A font whose header declares a unicode table but ends right after the glyphs is treated
+as having no table.
+
+Commands:
+    stats                     Print blank glyph and set pixel statistics instead of Verilog
+    export-glyphs             Write every glyph to DIR (default: glyphs) as U+XXXX.pbm,
+                              named after its first unicode table entry, or glyphNNNN.pbm
+                              when it has none, plus a mapping.txt listing
+                              \"index file code points...\" per glyph
+    diff                      List the glyph indices where two fonts of the same geometry
+                              differ, drawing both versions side by side with
+                              --show-glyphs, and exit nonzero if any do
+    delta                     Write only the glyphs of NEW_FONT that differ from BASE_FONT,
+                              which must have the same glyph size and count, for
+                              shipping an update: a glyphdelta ROM of the K changed
+                              glyph words, slot S holding the Sth, and a
+                              glyphdelta_index ROM of the glyph index each slot
+                              replaces. Firmware applies it by writing glyphdelta[S]
+                              over glyph glyphdelta_index[S] of the base font for every
+                              S < K, K given in a comment above the modules. The module
+                              goes to --output FILE or stdout, nothing being written
+                              when no glyph changed, and the changed count and bytes
+                              against the full font are printed on stderr. Transforms
+                              are an error, as they would change only the base font
+    combine-planes            Convert two fonts of the same geometry as one 2-bit-per-pixel
+                              font, each pixel being {second font's bit, first font's
+                              bit}, so rows and words are twice as wide; the first
+                              font's unicode table is kept
+    preview                   Draw every glyph under its index with # for set pixels, N
+                              glyphs to a row of the grid (default: 1), like
+                              showconsolefont; with --preview-png FILE, instead write
+                              the glyphs as a black on white PNG atlas N glyphs wide
+                              (default: 16), scaled by S (0 < S <= 1, default: 1): below
+                              1 the atlas is box-filtered to gray, a lossy thumbnail;
+                              with --preview-svg FILE, write them as an SVG sheet of
+                              one rect per set pixel, each glyph outlined and labelled
+                              with its first code point, or its index without a table
+    find                      List the glyphs matching the glyph-sized P1 or P4 PBM image
+                              PATTERN, or differing from it in at most N pixels, with
+                              their distance and code points, and exit nonzero if none
+                              does
+    query                     Draw the glyphs the unicode table maps C (a character or
+                              U+XXXX) to, or without a table the glyph at index C, under
+                              their indices, and exit nonzero if there are none
+    selftest                  Parse a small font built in memory and run it through every
+                              format, printing each check, and exit nonzero if any fails
+    import-png                Slice a PNG glyph sheet into WxH cells, left to right and
+                              top to bottom, and convert it like a font; pixels darker
+                              than 50% gray are set
+    import-glyphs             Build a font from the U+XXXX.pbm files in DIR, as written by
+                              export-glyphs, one glyph per file in code point order with
+                              a unicode table mapping each to its file's code point, and
+                              convert it like a font; every image must be WxH (--width
+                              and --height, by default the size of the first), and
+                              other files are ignored
+
+Options:
+    --check-padding           Only check that the bits past the width at the end of each
+                              row are clear, listing the first glyphs and rows where they
+                              are not (mispacked fonts show them as garbage on the right
+                              edge) and exiting nonzero if any are set
+    --check-codepage cp437|latin1
+                              Only list the characters of the code page that no glyph
+                              represents in the unicode table, exiting nonzero if any is
+                              missing; CP437 includes its graphic control range
+    --report-size             Only print, for each backend with the other options given,
+                              the bytes it would emit (memh includes its hex file), the
+                              arms of its glyph ROM and the bits it stores, plus the arms
+                              --collapse would leave; no files are written
+    --list-formats            Print the names accepted by --format and exit
+    --list-langs              Print the names accepted by --lang and exit
+    --quiet                   Hide the progress display shown on stderr when it is a
+                              terminal, in builds with the progress feature
+    --trace                   Print each parsing step on stderr: the detected version, the
+                              header fields, the bitmap bytes read, the table entries
+                              parsed and every anomaly recovered from
+    --check                   Only validate the font (header, geometry, charsize, table
+                              length, file length and the other options given) and print
+                              pass or FAIL per check, exiting nonzero if any fails
+    --max-width N             Refuse to convert glyphs wider than N pixels, after the
+                              transforms, rather than emit an unsynthesizably wide ROM
+    --max-height N            Refuse to convert glyphs higher than N pixels, likewise
+    --offset N                Start reading the font N bytes into the file, e.g. to pull
+                              one out of a ROM or firmware dump
+    --from-json               Read the font from JSON written by --format json, built with
+                              the json feature
+    --psf1-count N            Read N glyphs from a PSF1 font whatever its mode says; an
+                              escape hatch for nonconforming files with other counts
+    --psf1-table-stride N     Compatibility shim for nonstandard PSF1 fonts whose unicode
+                              table pads every 16-bit code point out to N bytes: read
+                              each from the start of its N-byte slot (default: 2)
+    --strip-table             Do not read the font's unicode table (or a trailing unimap),
+                              e.g. to convert a font whose table is broken; --comment
+                              labels, --emit-lookup, --subset-from and other table users
+                              then see a font without one
+    --compact-table           Read the unicode table with the parser that counts the
+                              entries first and reuses one buffer for every run of code
+                              points, for far fewer allocations on large tables; the
+                              table read is the same
+    --with-enable             Register the glyph output on clk, loading only while en is high
+    --with-reset              Register the glyph output on clk, clearing it while rst is asserted
+    --with-valid              Register the glyph output on clk, loading only while ren is high
+                              (and en, with --with-enable), and add an output valid that
+                              is ren delayed by one clock: the glyph for the codepoint
+                              presented with ren is on glyph while valid is high
+    --pipeline 1|2            Register the glyph output on clk (1, one clock of latency), or
+                              also register the codepoint into codepoint_r first (2, two
+                              clocks from codepoint to glyph) for high clock rates;
+                              without it the ROM is combinational unless another option
+                              registers it
+    --clock-edge pos|neg      The edge of clk every register loads on, posedge or negedge
+                              (default: pos); a combinational ROM has no clock to use it
+    --assign-style blocking|nonblocking
+                              The assignment registers load with in their always blocks:
+                              = or <= (default: nonblocking). A combinational ROM keeps its
+                              continuous assign, and the --compress rle decoder always
+                              uses <=, its state machine relying on it
+    --reset-active high|low   Polarity of rst (default: high)
+    --reset-sync              Sample rst on the clock edge (default)
+    --reset-async             Clear glyph as soon as rst is asserted
+    --format verilog|memh|byte-port|rawhex|oneline|mif|coe|json
+                              verilog: one glyph per address (default)
+                              memh: write the glyphs to charmap.hex and emit a module
+                              loading it with $readmemh from its INIT_FILE parameter,
+                              sized by its ADDR_WIDTH, DATA_WIDTH and DEPTH parameters
+                              byte-port: a flat ROM with one byte of the font per address
+                              rawhex: no Verilog, just \"index: hexword\" per glyph after a
+                              \"# N glyphs\" comment line
+                              oneline: just \"index hexword\" per glyph, one line each
+                              and nothing else, for line-by-line diffs of font changes
+                              mif: for Quartus, write the glyphs to charmap.mif as a
+                              Memory Initialization File (WIDTH, DEPTH, UNS addresses
+                              and data in --radix) and emit a module like memh's whose
+                              memory names it in a ram_init_file attribute
+                              coe: no Verilog, a Xilinx coefficients file for the Block
+                              Memory Generator, memory_initialization_radix from
+                              --radix and a memory_initialization_vector of the words
+                              json: built with the json feature, the whole font as JSON:
+                              version, width, height, each glyph's bytes in hex and the
+                              unicode table with code points as \"U+XXXX\" strings
+    --group-bytes N           Split every N bytes of hex digits, counted from the right, of
+                              the rawhex and oneline words and of the Verilog literals
+                              with _ (8N digits with --radix bin; decimal is left whole,
+                              and memh, mif, coe, VHDL, Python and Scala words are never
+                              split)
+    --group-sep C             With --group-bytes, split rawhex and oneline words with the
+                              character C instead of _
+    --no-wrapper              With --format mif, emit only the MIF, on stdout
+    --emit-selectable         Convert every font given, which must share the first one's
+                              glyph size, as modules glyphmap_font0, glyphmap_font1, ...
+                              under a glyphmap top module with an extra input font_sel
+                              choosing which drives glyph; font_sel has enough bits to
+                              number the fonts (at least 1), and codepoint enough for
+                              the largest font, the smaller ones answering blank past
+                              their last glyph. Only the first font goes through the
+                              transforms and checks above
+    --wrap-into TEMPLATE      Write TEMPLATE with the generated modules in place of each
+                              {{FONT_MODULE}}, e.g. a top module file that instantiates
+                              glyphmap; it is an error if the placeholder is missing
+    --init-file NAME          With --format memh or mif, write the glyphs to NAME instead of
+                              charmap.hex or charmap.mif; the module defaults to the same
+                              path, so it keeps pointing at the file written
+    --emit-rom-file BASE      With --format memh, write the module to BASE.v and the glyphs
+                              to BASE.hex instead of stdout and charmap.hex, the module
+                              loading BASE.hex by its file name, so it must be read from
+                              the same directory; --emit-header puts glyphmap.vh there
+                              too. Either all the files are written or, on an error, none
+    --lang verilog|vhdl|python|scala
+                              vhdl: emit a VHDL package declaring the font_rom array type
+                              and a FONT constant holding one word per glyph
+                              python: emit a Python module with WIDTH, HEIGHT, COUNT and
+                              WORD_WIDTH constants, a FONT list of one int per glyph for
+                              an Amaranth or Migen Memory, and with a unicode table a
+                              UNICODE dict from code point to glyph index
+                              scala: emit a Scala object Font with Width, Height, Count
+                              and WordWidth vals, a font Seq[BigInt] of one word per
+                              glyph for a Chisel VecInit ROM, and with a unicode table a
+                              unicode Map from code point to glyph index
+    --validate-roundtrip      Read the output back before writing it and exit with an
+                              error at the first word that differs from the glyph's: for
+                              rawhex, oneline, python, scala and, built with the json
+                              feature, json, where the font read back is compared glyph
+                              by glyph along with its unicode table
+    --vhdl-bitstring          With --lang vhdl, declare FONT as one std_logic_vector string
+                              of every glyph word, glyph 0 leftmost, and a glyph(index)
+                              function in the package body returning one word
+    --package NAME            Name of the VHDL package (default: font_pkg)
+    --emit-grid-json          Instead of a ROM, write the pixels of the glyphs given by
+                              --index as JSON for rendering figures: a 2D array of 0
+                              and 1 per glyph, one nested array per row, top row first
+    --index N[,N...]          The glyphs --emit-grid-json writes; one index gives its grid
+                              alone, several an array of grids in the order listed
+    --emit-density            Instead of a ROM, print the set-pixel count of every glyph, a
+                              line of index and count per glyph
+    --as-rom                  With --emit-density, write the counts as a glyphdensity ROM,
+                              its words wide enough for a glyph with every pixel set
+    --emit-present            Instead of the font, write a glyphpresent ROM with a bit per
+                              glyph, set if the glyph has any pixel set, for skipping
+                              blank cells; word K holds glyphs K*N up, the first in bit 0
+    --present-width N         Glyphs per --emit-present word (default: 32)
+    --emit-char-row           Instead of the font, write a glyphmap_rows module reading one
+                              row per access for raster scan-out, with inputs character
+                              and row (wide enough for the height) and an output rowdata
+                              of the width's pixels, no padding, leftmost pixel first; it
+                              reads a glyphrows ROM, one word per row, at address
+                              character * height + row, and a registered output config
+                              applies to that ROM
+    --emit-hashed             Instead of the font, write a glyphhash module turning a unicode
+                              code point into its glyph, for sparse fonts: a perfect hash,
+                              found when converting, gives each code point in the table a
+                              slot of its own in a ROM at most about twice as deep, no two
+                              colliding, and the slot stores the code point beside the
+                              glyph so any code point not in the table reads 0. The hash
+                              multiplies by two constants, in a comment in the module,
+                              and XORs a displacement read from a second small ROM; needs
+                              a table and an unregistered output
+    --signed-output           Declare the data outputs signed: Verilog ports as wire signed
+                              or reg signed, --emit-struct's glyph_t as packed signed, and
+                              the VHDL words as numeric_std signed; only the declarations
+                              change, every word keeps the same bit pattern
+    --align-glyphs N          With --format byte-port, pad each glyph to a multiple of N
+                              bytes (a power of two) so glyph K starts at K * padded size
+    --addr-shift N            With --format byte-port, place glyph K at address K << N, e.g.
+                              8 for glyphs on 256-byte boundaries; the bytes between one
+                              glyph and the next hold --blank-value (default 0), and 2^N
+                              must be at least the glyph size
+    --nibble-mode             With --format byte-port, store each byte as two 4-bit words,
+                              its high nibble at address 2K and its low nibble at 2K+1,
+                              so the address gains a bit
+    --base-address N          With --format byte-port, answer at addresses N onwards (decimal
+                              or 0x hex), word K at N + K, widening the address port to
+                              reach the last word; the arms compare against the offset
+                              addresses, so no subtractor is built. The ROM must end
+                              within a 32-bit address space
+    --target generic|ice40|ecp5
+                              ice40 and ecp5 always register the glyph output on clk, as
+                              block RAM reads are synchronous, and with --format memh
+                              mark the mem array (* rom_style = \"block\" *) for ice40 or
+                              (* syn_romstyle = \"EBR\" *) for ecp5 (default: generic)
+    --rom-style block|distributed|auto
+                              Put (* rom_style = \"...\" *) on every ROM's data output, or
+                              on the mem array with --format memh, to force how Vivado
+                              and similar tools build it (default: no attribute)
+    --radix hex|bin|dec       Radix of the emitted data literals (default: hex)
+    --hex-case upper|lower    Case of hex digits in literals and hex files (default: upper)
+    --word-endian big|little  Put the glyph's first byte in the most (default) or least
+                              significant byte of the data word
+    --bit-order msb|lsb       Keep the leftmost pixel of each byte in its most significant
+                              bit as PSF stores it (default), or reverse the bits of
+                              every byte so it lands in the least significant bit
+    --pad-width-to-byte       Pad each row of the glyph word out to whole bytes as PSF
+                              stores it, ceil(W/8)*8 bits per row whatever the width
+                              (default), for consumers built around byte-wide rows
+    --trim-padding-bits       Pack each glyph word from its pixels alone, width * height
+                              bits with no padding out to whole bytes per row: row 0 in
+                              the top width bits, each row's leftmost pixel first, so row
+                              R is word[(H-R)*W-1 -: W]. Not with byte-port, --bit-order
+                              lsb or --word-endian little
+    --bank-width N            Split the data into N-bit-wide sub-ROMs glyphmap_bank0,
+                              glyphmap_bank1, ... where bank K holds bits
+                              [K*N+N-1:K*N] (the last bank may be narrower); the top
+                              glyphmap module wires each bank to its slice of the output
+    --max-output-bits N       Fail if a ROM's data output, or each bank's with --bank-width,
+                              would be wider than N bits, for tools that choke on wide
+                              case values
+    --collapse                Merge runs of consecutive identical glyphs into a single
+                              range condition, reporting how many arms remain
+    --blanks-as-dontcare      Give the arms of glyphs without a set pixel the value W'bx so
+                              synthesis may pick whatever bits minimize the logic; in
+                              simulation those glyphs then read as x, which propagates
+                              into anything computed from them, so only use it when blank
+                              glyphs are never displayed
+    --no-default-arm          Let the last glyph end the chain instead of a trailing 0, when
+                              the glyph count is a power of two so every address is a
+                              glyph; other ROMs keep the 0 and say so on stderr
+    --invert-address          Make every ROM answer to the one's complement of its address,
+                              for decoders that drive the address inverted: glyph K is
+                              returned when codepoint is ~K
+    --reverse-address-bits    Make every ROM answer to its address with the bit order
+                              reversed, for a bit-reversed address bus: bit i of K becomes
+                              bit N-1-i of codepoint, so with a 4-bit codepoint glyph 1
+                              (4'b0001) is returned for 4'b1000 and glyph 6 (4'b0110)
+                              for itself; applied before --invert-address
+    --blank-value BYTE        Fill the default arm, the glyphs padding out --split-files
+                              parts and --emit-muxtree trees, and --align-glyphs padding
+                              with BYTE (e.g. 0xFF for a display lit by clear bits)
+                              instead of zero, repeated up from the least significant
+                              bit; the font's own blank glyphs are left as they are, and
+                              --invert-address does not change which addresses get it
+    --comment                 Annotate each arm with the code points its glyph represents;
+                              built with the unicode-names feature, each is followed by
+                              its Unicode character name, and list the glyphs the
+                              unicode table leaves without a code point
+    --embed-table-comment     Write the unicode table at the top of the module, one comment
+                              line per glyph listing its code points and sequences
+    --named-glyphs            Declare each glyph word as a localparam in its ROM module and
+                              return it by name: GLYPH_41 after the first code point of
+                              its table entry in hex, or GLYPH_INDEX_7 for a glyph with
+                              none or whose code point an earlier glyph already named
+    --compress rle            Store the glyphs run-length encoded and emit a decoder: pulse
+                              start with codepoint set, and glyph shifts in one pixel per
+                              clock until done rises a word-width of clocks later. Each
+                              run is a byte in glyphmap_rle_data, its pixel value in bit 7
+                              and its length minus one (1-128) in bits 6-0; runs restart
+                              at every glyph, whose first byte glyphmap_rle_index gives
+    --dual-port               Emit one glyph array with two read ports, codepoint_a/glyph_a
+                              and codepoint_b/glyph_b, both registered on clk, e.g. for
+                              text plus a cursor overlay; synthesis tools infer a dual-port
+                              block ROM from the initialized mem array, so the depth and
+                              width must fit one or the array becomes logic
+    --emit-muxtree            Select the glyph through a balanced tree of 2:1 muxes, one
+                              level per address bit, instead of a comparison chain;
+                              whether this synthesizes better varies by tool and target
+    --emit-column-scan        Instead of the font, write a glyphmap_columns module reading
+                              one column per access, for matrices driven a column at a
+                              time (e.g. MAX7219): inputs character and column (wide
+                              enough for the width), output coldata of the height's
+                              pixels, top pixel first, from a glyphcolumns ROM at
+                              character * width + column, and a registered output config
+                              applies to that ROM
+    --emit-initial-array      Hold each ROM's glyphs in a reg array filled by an initial
+                              block and read it with mem[codepoint], combinationally or
+                              on clk like the comparison chain it replaces; some
+                              simulators load this faster, and addresses past the last
+                              glyph read as x. It needs no $readmemh file, for flows that
+                              forbid one, and is about a quarter smaller than the chain
+                              (13.9 kB against 18.3 kB for 256 8x16 glyphs); a memh hex
+                              file of just the words is smaller still, at 8.4 kB
+    --emit-plain-init         The same as --emit-initial-array
+    --emit-lookup             Also emit a glyphlookup module turning a unicode code point
+                              into the glyph index it is drawn with, matching every code
+                              point mapped to a glyph (0 when none is); needs a table
+    --emit-sorted-index       Also emit a glyphindex ROM for a binary search in hardware: an
+                              entry per code point in the table, sorted by code point,
+                              each the pair {unicode, codepoint} of the code point and its
+                              glyph index (the first glyph, when several draw it). To look
+                              up U, binary search entries 0 to N-1, N given in a comment
+                              above the module, comparing U with the unicode field of
+                              each entry probed; needs a table
+    --fold-case upper|lower   With --emit-lookup, match each letter with the glyph of its
+                              upper (or lower) case form when the font draws one, e.g.
+                              'a' returns the 'A' glyph; only simple one-to-one case
+                              mappings are followed, so letters like 'ß' keep their own
+    --tb-vcd                  Also emit a glyphmap_tb testbench that presents every codepoint
+                              in turn for 10 ns, clocking the ROM at 100 MHz when it is
+                              registered, and dumps the run to glyphmap.vcd; it finishes just
+                              over 10 ns per glyph plus 10 ns per clock of latency after
+                              the start: iverilog -s glyphmap_tb glyphmap.v && vvp a.out
+    --emit-bbox               Also emit a glyphbbox ROM giving each glyph's tight bounding
+                              box as { empty, x_min, y_min, x_max, y_max }, with empty
+                              set (and the box zero) for blank glyphs
+    --emit-attr-rom           Also emit a glyphattr ROM giving each glyph an N-bit
+                              attribute (e.g. a text-mode color), zero unless filled
+                              from --attr-file
+    --attr-file FILE          Read the glyphattr words from FILE, one hex number per glyph
+                              and line (blank lines and # comments are skipped); its
+                              count must match the glyph count
+    --attr-width N            Width of the glyphattr words, 1 to 64 (default: 8)
+    --emit-struct             Also emit a SystemVerilog package glyph_pkg typing each glyph
+                              as glyph_t, a packed struct whose row field holds the rows
+                              top to bottom as row[0] to row[H-1], and a function
+                              glyph_rows(codepoint) returning it
+    --emit-header             With the Verilog formats, also write glyphmap.vh defining
+                              FONT_WIDTH, FONT_HEIGHT, FONT_COUNT (glyphs) and
+                              FONT_RASTER_BITS (width times height) for `include
+    --split-512               For a 512-glyph font, emit glyphs 0-255 as glyphmap_low and
+                              256-511 as glyphmap_high, both indexed by an 8-bit
+                              codepoint, with the top module choosing between them on
+                              its page input
+    --psf1-pages flat|split   How to read the 512 glyphs of a PSF1 font with MODE512 set:
+                              as one flat font (default), or as two alternate 256-glyph
+                              fonts, emitted like --split-512 with the page input
+                              choosing the font. VGA text mode picked the page from a
+                              bit of each cell's attribute, so some legacy fonts are
+                              really two fonts side by side rather than one of 512
+    --split-files N           Write the glyphs to at most N files charmap_0.v, charmap_1.v,
+                              ..., next to --output, each holding module glyphmap_partK
+                              for a run of a power-of-two number of glyphs; the glyphmap
+                              module instantiates every part on the low codepoint bits and
+                              picks one with the high bits
+    --output-width N          Zero-extend every glyph word to N bits, which must be at
+                              least the glyph size in bits
+    --with-parity even|odd    Append a parity bit over each word as its new least
+                              significant bit, so the data port is one bit wider than
+                              the glyph; consumers must drop or check it
+    --bitplanes N             Repeat every pixel N times across its row, so each pixel is N
+                              identical bits (like the planes combine-planes interleaves)
+                              and rows and words are N times as wide; this only
+                              replicates the one font, it cannot give planes of their own
+    --strip-control-chars     Clean up a unicode table damaged by a text-mode editor: drop
+                              the C0 control characters, such as stray carriage returns,
+                              from every entry before anything else reads the table
+    --add-identity-table      If the font has no unicode table, map each glyph index to
+                              the code point with the same value
+    --normalize               Recenter every glyph in its cell by its tight bounding box,
+                              an odd spare column or row going right or below; blank
+                              glyphs are left alone. Runs before --trim-width
+    --shadow n|ne|e|se|s|sw|w|nw
+                              Add a drop shadow: a copy of every glyph moved one pixel that
+                              way, ORed into the glyph; shadow pixels moved past the cell
+                              edge are dropped, not wrapped. Runs after --normalize
+    --shadow-plane            With --shadow, keep the shadow as a second bit plane instead,
+                              each pixel becoming the pair {shadow, glyph} as with
+                              combine-planes, doubling the width
+    --rotate 180              Turn every glyph upside down, for displays mounted that way;
+                              width and height are unchanged. Runs after --shadow
+    --trim-width              Crop the columns left and right of the outermost pixel set
+                              in any glyph, shrinking width and the row stride
+    --subset-from FILE        Keep only the glyphs drawing a character of the UTF-8 text in
+                              FILE (a leading byte order mark and line breaks are
+                              ignored), in font order; needs a unicode table
+    --sort-by-codepoint       Reorder the glyphs by the first code point in their unicode
+                              table entry, glyphs without one last; the table is
+                              reordered with them, so --comment and export-glyphs
+                              report the new indices
+    --codepage FILE           Rebuild the font in the order of the code page in FILE, one
+                              \"INDEX U+XXXX\" line per index (# starts a comment): glyph
+                              INDEX becomes the glyph the unicode table draws U+XXXX
+                              with, and the table maps it to U+XXXX alone. Indices not
+                              listed are blank, as are code points the font lacks, each
+                              with a warning; an index listed twice is an error
+    --range U+XXXX-U+YYYY     Emit only the glyphs drawing the code points of the inclusive
+                              range, glyph K drawing U+XXXX + K, using the unicode
+                              table; code points the font lacks are blank, each with a
+                              warning. An end may also be given as a literal character,
+                              as in A-Z
+    --trim-trailing-blanks    Leave out the blank glyphs after the last glyph with a set
+                              pixel, shrinking the glyph count and so maybe the address,
+                              and print on stderr how many went and the bytes saved;
+                              unlike --drop-blanks, every glyph keeps its index
+    --drop-blanks             Leave out every glyph without a set pixel, so the ROM holds
+                              only the rest at packed indices, and print on stderr the
+                              packed index of each original glyph (\"remap 65 -> 40\", or
+                              \"-> blank\") and the bytes saved
+    --pad-to N                Append blank glyphs, filled with --blank-value, until the font
+                              has N glyphs, to match a fixed ROM depth; it is an error if
+                              it already has more. Runs after --drop-blanks";
+
+/// Prints the library's debug log on stderr, installed by --trace.
+struct TraceLogger;
+
+impl log::Log for TraceLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("trace: {}", record.args());
         }
     }
-    Ok(())
-}
 
-#[derive(Debug)]
-enum ParseError {
-    IoError(std::io::Error),
-    NotPSF,
-    UnsupportedVersion,
+    fn flush(&self) {}
 }
 
-impl From<std::io::Error> for ParseError {
-    fn from(err: std::io::Error) -> Self {
-        Self::IoError(err)
+static TRACE_LOGGER: TraceLogger = TraceLogger;
+
+/// Names the extension of a file called .psf or .psfu that turns out to have no PSF magic,
+/// since the generic error would not say why the file was expected to be a font.
+fn explain_extension(path: &str, err: ParseError) -> ParseError {
+    let extension = Path::new(path)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match (err, extension.as_deref()) {
+        (ParseError::NotPSF, Some(extension @ ("psf" | "psfu"))) => {
+            ParseError::InvalidArgument(format!(
+                "{} has a .{} extension but neither the PSF1 nor the PSF2 magic",
+                path, extension
+            ))
+        }
+        (err, _) => err,
     }
 }
 
-#[derive(Default, Debug)]
-struct TableEntry {
-    represented: Vec<char>,
-    sequences: Vec<char>,
+/// Reads the font, PNG sheet or JSON at `path`, starting `--offset` bytes in.
+fn read_font(path: &str, opts: &Options) -> Result<(PSF, Vec<Warning>), ParseError> {
+    let (mut psf_file, len) = open_input(path)?;
+    if opts.offset >= len {
+        return Err(ParseError::InvalidArgument(format!(
+            "--offset {} is past the end of the {}-byte file",
+            opts.offset, len
+        )));
+    }
+    psf_file.seek(SeekFrom::Start(opts.offset))?;
+    Ok(match opts.command {
+        Command::ImportPng => (
+            PSF::from_png(BufReader::new(psf_file), opts.cell_width, opts.cell_height)?,
+            vec![],
+        ),
+        #[cfg(feature = "json")]
+        _ if opts.from_json => (PSF::from_json(BufReader::new(psf_file))?, vec![]),
+        _ => PSF::parse_reader_with_warnings(psf_file, opts)
+            .map_err(|err| explain_extension(path, err))?,
+    })
 }
 
-#[derive(Debug)]
-enum Version {
-    PSF1,
-    PSF2,
+/// Reads a second font named on the command line the same way as the first, printing its
+/// warnings against its path.
+fn read_other_font(path: &str, opts: &Options) -> Result<PSF, ParseError> {
+    let (psf, warnings) = read_font(path, opts)?;
+    for warning in &warnings {
+        if !matches!(warning, Warning::StrayByte(_)) {
+            eprintln!("warning: {}: {}", path, warning);
+        }
+    }
+    Ok(psf)
 }
 
-#[derive(Debug)]
-struct PSF {
-    version: Version,
-    charsize: u32,
-    height: u32,
-    width: u32,
-    bitmap: Vec<u8>,
-    table: Option<Vec<TableEntry>>,
+fn print_notes(notes: &[Note]) {
+    for note in notes {
+        eprintln!("{}", note);
+    }
 }
 
-impl PSF {
-    const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
-    const PSF1_MODE512: u8 = 0x01;
-    const PSF1_MODEHASTAB: u8 = 0x02;
-    const PSF1_MODEHASSEQ: u8 = 0x04;
-    const PSF1_MAXMODE: u8 = 0x05;
-    const PSF1_SEPARATOR: u16 = 0xFFFF;
-    const PSF1_STARTSEQ: u16 = 0xFFFE;
-
-    const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
-    const PSF2_SEPARATOR: u8 = 0xFF;
-    const PSF2_STARTSEQ: u8 = 0xFE;
-    const PSF2_MAXVERSION: u32 = 0;
-    const PSF2_HASUNICODETABLE: u32 = 0x01;
-
-    fn parse_table<'a>(table: &'a [u8], version: &Version) -> Vec<TableEntry> {
-        let mut entries = vec![];
-        let mut current_entry = TableEntry::default();
-        let mut sequence_started = false;
-        match version {
-            Version::PSF1 => {
-                let mut codepoints = vec![];
-                for i in (0..table.len()).step_by(2) {
-                    let codepoint = u16::from_le_bytes(table[i..=i + 1].try_into().unwrap());
-                    if codepoint == Self::PSF1_SEPARATOR || codepoint == Self::PSF1_STARTSEQ {
-                        let mut chars = decode_utf16(codepoints).map(Result::unwrap).collect();
-                        if !sequence_started {
-                            current_entry.represented = chars;
-                        } else {
-                            current_entry.sequences.append(&mut chars);
-                        }
-                        codepoints = vec![];
+trait Input: Read + Seek {}
 
-                        if codepoint == Self::PSF1_SEPARATOR {
-                            sequence_started = false;
-                            entries.push(current_entry);
-                            current_entry = TableEntry::default();
-                        } else {
-                            sequence_started = true;
+impl<T: Read + Seek> Input for T {}
+
+/// Opens the font file, or with the net feature downloads it when `path` is a URL, giving
+/// its length too.
+fn open_input(path: &str) -> Result<(Box<dyn Input>, u64), ParseError> {
+    #[cfg(feature = "net")]
+    if psf2verilog::net::is_url(path) {
+        let bytes = psf2verilog::net::fetch(path)?;
+        let len = bytes.len() as u64;
+        return Ok((Box::new(io::Cursor::new(bytes)), len));
+    }
+    let file = File::open(Path::new(path))?;
+    let len = file.metadata()?.len();
+    Ok((Box::new(file), len))
+}
+
+fn main() -> Result<(), ParseError> {
+    let opts = Options::parse(env::args().skip(1))?;
+    if opts.trace && log::set_logger(&TRACE_LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+    if opts.list_formats || opts.list_langs {
+        if opts.list_formats {
+            for name in format_names() {
+                println!("{}", name);
+            }
+        }
+        if opts.list_langs {
+            for lang in langs() {
+                println!("{}", lang.name());
+            }
+        }
+        return Ok(());
+    }
+    if let Command::SelfTest = opts.command {
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        if !PSF::selftest(&mut out)? {
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    match &opts.path {
+        Some(path) => {
+            let progress = Progress::new(opts.quiet);
+            progress.stage(format!("reading {}", path));
+            let (mut psf, warnings) = match opts.command {
+                Command::ImportGlyphs => (
+                    PSF::import_glyphs(Path::new(path), opts.cell_width, opts.cell_height)?,
+                    vec![],
+                ),
+                _ => read_font(path, &opts)?,
+            };
+            if !opts.check {
+                for warning in &warnings {
+                    if !matches!(warning, Warning::StrayByte(_)) {
+                        eprintln!("warning: {}", warning);
+                    }
+                }
+            }
+            if opts.strip_control_chars {
+                let stripped = psf.strip_control_chars();
+                if stripped > 0 {
+                    eprintln!(
+                        "warning: dropped {} control characters from the unicode table",
+                        stripped
+                    );
+                }
+            }
+            if let Command::CombinePlanes = opts.command {
+                let other_path = opts.other_path.as_ref().ok_or_else(|| {
+                    ParseError::MissingValue("second font to combine".to_string())
+                })?;
+                psf.combine_planes(&read_other_font(other_path, &opts)?)?;
+            }
+            if let Some(planes) = opts.bitplanes {
+                psf.replicate_planes(planes);
+            }
+            if opts.add_identity_table {
+                psf.add_identity_table();
+            }
+            if opts.normalize {
+                psf.normalize();
+            }
+            if let Some(offset) = opts.shadow {
+                psf.shadow(offset, opts.shadow_plane);
+            }
+            if opts.rotate_180 {
+                psf.rotate_180();
+            }
+            if opts.trim_width {
+                psf.trim_width();
+            }
+            if let Some(subset_from) = &opts.subset_from {
+                psf.subset(&subset_codepoints(&fs::read(subset_from)?)?)?;
+            }
+            if opts.sort_by_codepoint {
+                psf.sort_by_codepoint()?;
+            }
+            if let Some(codepage_map) = &opts.codepage_map {
+                let map = parse_codepage_map(&fs::read_to_string(codepage_map)?)?;
+                for (index, c) in psf.retarget(&map)? {
+                    eprintln!(
+                        "warning: no glyph for U+{:04X} at code page index {}, left blank",
+                        c as u32, index
+                    );
+                }
+            }
+            if let Some((start, end)) = opts.range {
+                for (index, c) in psf.retarget_range(start, end)? {
+                    eprintln!(
+                        "warning: no glyph for U+{:04X} at index {}, left blank",
+                        c as u32, index
+                    );
+                }
+            }
+            if opts.trim_trailing_blanks {
+                let trimmed = psf.trim_trailing_blanks()?;
+                eprintln!(
+                    "trimmed {} trailing blank glyphs, saving {} bytes",
+                    trimmed,
+                    trimmed * psf.geometry().charsize as usize
+                );
+            }
+            if opts.drop_blanks {
+                let charsize = psf.geometry().charsize as usize;
+                let remap = psf.drop_blanks()?;
+                let dropped = remap.iter().filter(|packed| packed.is_none()).count();
+                for (glyph, packed) in remap.iter().enumerate() {
+                    match packed {
+                        Some(packed) => eprintln!("remap {} -> {}", glyph, packed),
+                        None => eprintln!("remap {} -> blank", glyph),
+                    }
+                }
+                eprintln!(
+                    "dropped {} blank glyphs, saving {} bytes",
+                    dropped,
+                    dropped * charsize
+                );
+            }
+            if let Some(count) = opts.pad_to {
+                psf.pad_to(count, opts.blank_value.unwrap_or(0))?;
+            }
+            if opts.check {
+                let checks = psf.check(&warnings, &opts);
+                let mut failed = 0;
+                for (name, result) in &checks {
+                    match result {
+                        Ok(()) => println!("pass  {}", name),
+                        Err(reason) => {
+                            failed += 1;
+                            println!("FAIL  {}: {}", name, reason);
                         }
-                    } else {
-                        codepoints.push(codepoint);
                     }
                 }
+                println!(
+                    "{} of {} checks passed",
+                    checks.len() - failed,
+                    checks.len()
+                );
+                process::exit(if failed == 0 { 0 } else { 1 });
             }
-            Version::PSF2 => {
-                let mut codepoints = vec![];
-                for i in 0..table.len() {
-                    let codepoint = table[i];
-                    if codepoint == Self::PSF2_SEPARATOR || codepoint == Self::PSF2_STARTSEQ {
-                        let mut chars = std::str::from_utf8(&codepoints).unwrap().chars().collect();
-                        if !sequence_started {
-                            current_entry.represented = chars;
-                        } else {
-                            current_entry.sequences.append(&mut chars);
+            if let Some(codepage) = opts.check_codepage {
+                let missing = psf.missing_from(codepage);
+                for (byte, c) in &missing {
+                    println!("missing 0x{:02X} U+{:04X}", byte, *c as u32);
+                }
+                println!(
+                    "{} of {} code page characters have a glyph",
+                    codepage.mapping().len() - missing.len(),
+                    codepage.mapping().len()
+                );
+                process::exit(if missing.is_empty() { 0 } else { 1 });
+            }
+            if opts.check_padding {
+                const SHOWN: usize = 8;
+                let offenders = psf.padding_offenders();
+                for (glyph, row, padding) in offenders.iter().take(SHOWN) {
+                    println!("glyph {} row {}: padding bits {:#04x}", glyph, row, padding);
+                }
+                if offenders.len() > SHOWN {
+                    println!("... and {} more rows", offenders.len() - SHOWN);
+                }
+                let mut glyphs: Vec<usize> = offenders.iter().map(|&(glyph, ..)| glyph).collect();
+                glyphs.dedup();
+                println!(
+                    "{} rows in {} glyphs have set bits past the {}-pixel width",
+                    offenders.len(),
+                    glyphs.len(),
+                    psf.geometry().width
+                );
+                process::exit(if offenders.is_empty() { 0 } else { 1 });
+            }
+            psf.check_options(&opts)?;
+            if opts.report_size {
+                let stdout = io::stdout();
+                let mut out = BufWriter::new(stdout.lock());
+                psf.write_size_report(&opts, &mut out)?;
+                return Ok(out.flush()?);
+            }
+            progress.stage(format!("writing {} glyphs", psf.glyph_count()));
+            let stdout = io::stdout();
+            let mut out = BufWriter::new(stdout.lock());
+            match opts.command {
+                Command::Convert
+                | Command::ImportPng
+                | Command::ImportGlyphs
+                | Command::CombinePlanes => {
+                    let mut others = vec![];
+                    for path in &opts.select_paths {
+                        let other = read_other_font(path, &opts)?;
+                        let (a, b) = (psf.geometry(), other.geometry());
+                        if (a.width, a.height) != (b.width, b.height) {
+                            return Err(ParseError::InvalidArgument(format!(
+                                "--emit-selectable: {} is {}x{} but the first font is {}x{}",
+                                path, b.width, b.height, a.width, a.height
+                            )));
                         }
-                        codepoints = vec![];
-
-                        if codepoint == Self::PSF2_SEPARATOR {
-                            sequence_started = false;
-                            entries.push(current_entry);
-                            current_entry = TableEntry::default();
+                        others.push(other);
+                    }
+                    let emit = |mut out: &mut dyn Write| {
+                        if opts.emit_selectable {
+                            psf.write_selectable(&others, &opts, &mut out)
+                        } else if opts.emit_grid_json {
+                            psf.write_grid_json(&opts.grid_indices, &mut out)
+                        } else if opts.emit_density {
+                            psf.write_density(&opts, &mut out)
+                        } else if opts.emit_present {
+                            psf.write_present(&opts, &mut out)
+                        } else if opts.emit_hashed {
+                            psf.write_hashed(&opts, &mut out)
+                        } else if opts.emit_char_row {
+                            psf.write_char_row(&opts, &mut out)
+                        } else if opts.emit_column_scan {
+                            psf.write_column_scan(&opts, &mut out)
                         } else {
-                            sequence_started = true;
+                            backend(&opts).emit(&psf, &opts, out)
+                        }
+                    };
+                    let mut mismatch = None;
+                    let (written, notes) = collect_notes(|| -> Result<(), ParseError> {
+                        match (&opts.emit_rom_file, &opts.wrap_into) {
+                            (Some(base), _) => psf.write_rom_files(&opts, base)?,
+                            (None, Some(template_path)) => {
+                                let template = fs::read_to_string(template_path)?;
+                                if !template.contains(WRAP_PLACEHOLDER) {
+                                    return Err(ParseError::InvalidArgument(format!(
+                                        "--wrap-into template {} has no {} placeholder",
+                                        template_path, WRAP_PLACEHOLDER
+                                    )));
+                                }
+                                let mut module = vec![];
+                                emit(&mut module)?;
+                                let module = String::from_utf8_lossy(&module);
+                                write!(
+                                    out,
+                                    "{}",
+                                    template.replace(WRAP_PLACEHOLDER, module.trim_end())
+                                )?;
+                            }
+                            (None, None) if opts.validate_roundtrip => {
+                                let mut output = vec![];
+                                emit(&mut output)?;
+                                match psf.validate_roundtrip(&opts, &output) {
+                                    Ok(()) => out.write_all(&output)?,
+                                    Err(difference) => mismatch = Some(difference),
+                                }
+                            }
+                            (None, None) => emit(&mut out)?,
+                        }
+                        Ok(())
+                    });
+                    print_notes(&notes);
+                    written?;
+                    if let Some(difference) = mismatch {
+                        eprintln!("--validate-roundtrip: {}", difference);
+                        process::exit(1);
+                    }
+                }
+                Command::Stats => psf.write_stats(&mut out)?,
+                Command::Preview => match (&opts.preview_png, &opts.preview_svg) {
+                    (None, None) => psf.write_preview(opts.preview_cols.unwrap_or(1), &mut out)?,
+                    (png, svg) => {
+                        if let Some(path) = png {
+                            psf.write_preview_png(
+                                opts.preview_cols.unwrap_or(16),
+                                opts.preview_scale.unwrap_or(1.0),
+                                BufWriter::new(File::create(path)?),
+                            )?;
+                        }
+                        if let Some(path) = svg {
+                            psf.write_preview_svg(
+                                opts.preview_cols.unwrap_or(16),
+                                &mut BufWriter::new(File::create(path)?),
+                            )?;
+                        }
+                    }
+                },
+                Command::Diff => {
+                    let other_path = opts.other_path.as_ref().ok_or_else(|| {
+                        ParseError::MissingValue("second font to diff".to_string())
+                    })?;
+                    let other = read_other_font(other_path, &opts)?;
+                    let differing = psf.write_diff(&other, opts.show_glyphs, &mut out)?;
+                    out.flush()?;
+                    if differing != 0 {
+                        process::exit(1);
+                    }
+                }
+                Command::Delta => {
+                    let new_path = opts.other_path.as_ref().ok_or_else(|| {
+                        ParseError::MissingValue("new font for the delta".to_string())
+                    })?;
+                    let new = read_other_font(new_path, &opts)?;
+                    let mut module = vec![];
+                    let (changed, notes) =
+                        collect_notes(|| psf.write_delta(&new, &opts, &mut module));
+                    print_notes(&notes);
+                    let changed = changed?;
+                    let charsize = psf.geometry().charsize as usize;
+                    eprintln!(
+                        "{} of {} glyphs changed: {} bytes of glyphs against {} for the font",
+                        changed.len(),
+                        psf.glyph_count(),
+                        changed.len() * charsize,
+                        psf.glyph_count() as usize * charsize
+                    );
+                    if !changed.is_empty() {
+                        match &opts.output {
+                            Some(path) => fs::write(path, module)?,
+                            None => out.write_all(&module)?,
                         }
-                    } else {
-                        codepoints.push(codepoint);
                     }
                 }
+                Command::Find => {
+                    let pattern_path = opts.pattern_path.as_ref().ok_or_else(|| {
+                        ParseError::MissingValue("--pbm pattern to find".to_string())
+                    })?;
+                    let (width, height, pattern) = parse_pbm(&fs::read(pattern_path)?)?;
+                    let matches =
+                        psf.write_matches((width, height, &pattern), opts.tolerance, &mut out)?;
+                    out.flush()?;
+                    if matches == 0 {
+                        process::exit(1);
+                    }
+                }
+                Command::Query => {
+                    let c = opts
+                        .query_char
+                        .ok_or_else(|| ParseError::MissingValue("--char to look up".to_string()))?;
+                    let found = psf.write_query(c, &mut out)?;
+                    out.flush()?;
+                    if found == 0 {
+                        process::exit(1);
+                    }
+                }
+                Command::SelfTest => unreachable!("selftest reads no font"),
+                Command::ExportGlyphs => {
+                    psf.export_glyphs(Path::new(opts.out_dir.as_deref().unwrap_or("glyphs")))?
+                }
             }
+            out.flush()?;
+            progress.finish(format!("done with {} glyphs", psf.glyph_count()));
         }
-        entries
-    }
-
-    fn into_verilog(&self) {
-        let length = self.bitmap.len() as u32 / self.charsize;
-        let input_width = (length as f64).log2().ceil() as u8;
-        let output_width = self.charsize * 8;
-        println!("module glyphmap ( input wire [{}:0] codepoint, output wire [{}:0] glyph );", input_width - 1, output_width - 1);
-        print!("assign glyph = ");
-        for i in 0..length as usize {
-            let mut s = String::with_capacity(output_width as usize);
-            for j in 0..(self.charsize as usize) {
-                s.push_str(&format!(
-                    "{:0>2X}",
-                    self.bitmap[i * self.charsize as usize + j]
-                ));
-            }
-            if i != 0 {
-                print!("    : ");
-            }
-            println!(
-                "codepoint == {}'b{:0>input_width$b} ? {}'h{}",
-                input_width,
-                i,
-                output_width,
-                s,
-                input_width = input_width as usize
-            );
-        }
-        println!("    : 0;");
-        println!("endmodule");
-    }
-}
-
-impl TryFrom<File> for PSF {
-    type Error = ParseError;
-    fn try_from(mut psf_file: File) -> Result<Self, Self::Error> {
-        let mut magic = [0u8; 4];
-        psf_file.read_exact(&mut magic)?;
-        if magic[0..2] == Self::PSF1_MAGIC {
-            let version = Version::PSF1;
-            let mode = magic[2];
-            let height = magic[3];
-            let width = 8;
-            let length = if mode & Self::PSF1_MODE512 != 0 {
-                512
-            } else {
-                256
-            };
-            let charsize = height as usize;
-            let mut bitmap = vec![0u8; charsize * length];
-            psf_file.read_exact(&mut bitmap)?;
-            let mut table_buf = vec![];
-            let table = if mode & Self::PSF1_MODEHASTAB != 0 {
-                psf_file.read_to_end(&mut table_buf)?;
-                Some(Self::parse_table(&table_buf, &version))
-            } else {
-                None
-            };
-            Ok(PSF {
-                version,
-                charsize: charsize as u32,
-                height: height as u32,
-                width,
-                bitmap,
-                table,
-            })
-        } else if magic == Self::PSF2_MAGIC {
-            let version = Version::PSF2;
-            let mut rest_of_header = [0u8; 7 * 4];
-            psf_file.read_exact(&mut rest_of_header)?;
-            let header_version = u32::from_le_bytes(rest_of_header[0..4].try_into().unwrap());
-            let header_size = u32::from_le_bytes(rest_of_header[4..8].try_into().unwrap());
-            let flags = u32::from_le_bytes(rest_of_header[8..12].try_into().unwrap());
-            let length = u32::from_le_bytes(rest_of_header[12..16].try_into().unwrap());
-            let charsize = u32::from_le_bytes(rest_of_header[16..20].try_into().unwrap());
-            let height = u32::from_le_bytes(rest_of_header[20..24].try_into().unwrap());
-            let width = u32::from_le_bytes(rest_of_header[24..28].try_into().unwrap());
-            if header_size >= 32 {
-                // Skip the remainder of the header
-                psf_file.seek(SeekFrom::Current((header_size - 32) as i64))?;
-            } else {
-                eprintln!("header_size should be >= 32 but = {}", header_size);
-            }
-            let mut bitmap = vec![0u8; (charsize * length) as usize];
-            psf_file.read_exact(&mut bitmap)?;
-
-            let table = if flags & Self::PSF2_HASUNICODETABLE > 0 {
-                let mut table_buf = vec![];
-                psf_file.read_to_end(&mut table_buf)?;
-                Some(Self::parse_table(&mut table_buf, &version))
-            } else {
-                None
-            };
-
-            if header_version > Self::PSF2_MAXVERSION {
-                Err(ParseError::UnsupportedVersion)
-            } else {
-                Ok(PSF {
-                    version,
-                    charsize,
-                    height,
-                    width,
-                    bitmap,
-                    table,
-                })
-            }
-        } else {
-            Err(ParseError::NotPSF)
+        None => {
+            eprintln!("{}", USAGE);
         }
     }
+    Ok(())
 }
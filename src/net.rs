@@ -0,0 +1,17 @@
+use super::ParseError;
+
+/// Whether the input path names an HTTP(S) URL rather than a file.
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+/// Downloads the font at `url` into memory, following redirects; an error status fails.
+pub fn fetch(url: &str) -> Result<Vec<u8>, ParseError> {
+    let mut response = ureq::get(url)
+        .call()
+        .map_err(|err| ParseError::Http(format!("{}: {}", url, err)))?;
+    response
+        .body_mut()
+        .read_to_vec()
+        .map_err(|err| ParseError::Http(format!("{}: {}", url, err)))
+}
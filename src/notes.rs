@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::fmt;
+
+/// Something worth telling the user about a conversion that is not a problem with the font,
+/// such as how much `--collapse` saved. Emitters record these as they write; a caller who
+/// wants them runs the conversion under `collect_notes`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Note {
+    /// A module's words merged into fewer comparison arms under `--collapse`
+    Collapsed {
+        module: String,
+        words: usize,
+        arms: usize,
+    },
+    /// `--no-default-arm` kept the default arm of a module whose words leave addresses over
+    DefaultArmKept {
+        module: String,
+        words: usize,
+        addresses: usize,
+    },
+    /// `--align-glyphs` padding in the byte-port ROM
+    Aligned {
+        charsize: usize,
+        aligned: usize,
+        padding: usize,
+        glyph_bytes: usize,
+    },
+    /// The size of the glyphs before and after `--compress rle`
+    Compressed { bytes: usize, runs: usize },
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Collapsed { words, arms, .. } => {
+                write!(f, "collapsed {} arms into {}", words, arms)
+            }
+            Self::DefaultArmKept {
+                module,
+                words,
+                addresses,
+            } => write!(
+                f,
+                "{}: keeping the default arm, {} words do not cover all {} addresses",
+                module, words, addresses
+            ),
+            Self::Aligned {
+                charsize,
+                aligned,
+                padding,
+                glyph_bytes,
+            } => write!(
+                f,
+                "aligned {}-byte glyphs to {} bytes: {} padding bytes ({:.1}% overhead)",
+                charsize,
+                aligned,
+                padding,
+                *padding as f64 * 100.0 / *glyph_bytes as f64
+            ),
+            Self::Compressed { bytes, runs } => write!(
+                f,
+                "compressed {} bytes of glyphs into {} run bytes",
+                bytes, runs
+            ),
+        }
+    }
+}
+
+thread_local! {
+    static NOTES: RefCell<Option<Vec<Note>>> = const { RefCell::new(None) };
+}
+
+/// Records a note for the innermost `collect_notes` on this thread; with none running, the
+/// note is dropped.
+pub(crate) fn note(note: Note) {
+    NOTES.with(|notes| {
+        if let Some(notes) = notes.borrow_mut().as_mut() {
+            notes.push(note);
+        }
+    });
+}
+
+/// Runs `f`, returning its result along with every note recorded while it ran.
+pub fn collect_notes<T>(f: impl FnOnce() -> T) -> (T, Vec<Note>) {
+    let outer = NOTES.with(|notes| notes.replace(Some(vec![])));
+    let result = f();
+    let collected = NOTES.with(|notes| notes.replace(outer));
+    (result, collected.unwrap_or_default())
+}
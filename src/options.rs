@@ -0,0 +1,712 @@
+use super::{Codepage, ParseError};
+use std::convert::TryFrom;
+
+#[derive(Default, Debug)]
+pub struct Options {
+    pub command: Command,
+    pub path: Option<String>,
+    pub other_path: Option<String>,
+    pub show_glyphs: bool,
+    pub pattern_path: Option<String>,
+    pub tolerance: usize,
+    pub preview_cols: Option<usize>,
+    pub preview_png: Option<String>,
+    pub preview_scale: Option<f64>,
+    pub preview_svg: Option<String>,
+    pub query_char: Option<char>,
+    pub offset: u64,
+    pub check: bool,
+    pub check_codepage: Option<Codepage>,
+    pub check_padding: bool,
+    pub list_formats: bool,
+    pub report_size: bool,
+    pub list_langs: bool,
+    pub psf1_count: Option<usize>,
+    pub psf1_table_stride: Option<usize>,
+    pub strip_table: bool,
+    pub compact_table: bool,
+    pub with_enable: bool,
+    pub with_valid: bool,
+    pub with_reset: bool,
+    pub reset_active_low: bool,
+    pub reset_async: bool,
+    pub pipeline: Option<usize>,
+    pub format: Format,
+    pub target: Target,
+    pub rom_style: Option<&'static str>,
+    pub lang: Lang,
+    pub package: Option<String>,
+    pub init_file: Option<String>,
+    pub no_wrapper: bool,
+    pub embed_table_comment: bool,
+    pub group_bytes: Option<usize>,
+    pub group_sep: Option<char>,
+    pub vhdl_bitstring: bool,
+    pub align_glyphs: Option<usize>,
+    pub addr_shift: Option<u32>,
+    pub nibble_mode: bool,
+    pub radix: Radix,
+    pub hex_lowercase: bool,
+    pub bank_width: Option<usize>,
+    pub output_width: Option<usize>,
+    pub parity: Option<Parity>,
+    pub collapse: bool,
+    pub compress: Option<Compression>,
+    pub no_default_arm: bool,
+    pub invert_address: bool,
+    pub reverse_address_bits: bool,
+    pub base_address: Option<usize>,
+    pub wrap_into: Option<String>,
+    pub emit_selectable: bool,
+    pub max_output_bits: Option<usize>,
+    pub signed_output: bool,
+    pub select_paths: Vec<String>,
+    pub blank_value: Option<u8>,
+    pub comment: bool,
+    pub named_glyphs: bool,
+    pub emit_muxtree: bool,
+    pub emit_column_scan: bool,
+    pub emit_initial_array: bool,
+    pub emit_lookup: bool,
+    pub fold_case: Option<FoldCase>,
+    pub emit_bbox: bool,
+    pub emit_header: bool,
+    pub emit_attr_rom: bool,
+    pub emit_struct: bool,
+    pub attr_file: Option<String>,
+    pub attr_width: Option<usize>,
+    pub split_512: bool,
+    pub psf1_pages: Psf1Pages,
+    pub clock_edge: ClockEdge,
+    pub assign_style: AssignStyle,
+    pub split_files: Option<usize>,
+    pub word_endian: Endian,
+    pub lsb_first: bool,
+    pub add_identity_table: bool,
+    pub trim_width: bool,
+    pub bitplanes: Option<u32>,
+    pub sort_by_codepoint: bool,
+    pub normalize: bool,
+    pub blanks_as_dontcare: bool,
+    pub shadow: Option<(isize, isize)>,
+    pub shadow_plane: bool,
+    pub rotate_180: bool,
+    pub drop_blanks: bool,
+    pub from_json: bool,
+    pub quiet: bool,
+    pub trace: bool,
+    pub dual_port: bool,
+    pub subset_from: Option<String>,
+    pub codepage_map: Option<String>,
+    pub range: Option<(char, char)>,
+    pub emit_grid_json: bool,
+    pub tb_vcd: bool,
+    pub emit_rom_file: Option<String>,
+    pub emit_density: bool,
+    pub as_rom: bool,
+    pub emit_present: bool,
+    pub strip_control_chars: bool,
+    pub validate_roundtrip: bool,
+    pub pad_to: Option<u32>,
+    pub emit_sorted_index: bool,
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub output: Option<String>,
+    pub trim_trailing_blanks: bool,
+    pub emit_hashed: bool,
+    pub trim_padding_bits: bool,
+    pub emit_char_row: bool,
+    pub present_width: Option<usize>,
+    pub grid_indices: Vec<usize>,
+    pub out_dir: Option<String>,
+    pub cell_width: u32,
+    pub cell_height: u32,
+}
+
+#[derive(Debug, Default)]
+pub enum Command {
+    #[default]
+    Convert,
+    Stats,
+    ExportGlyphs,
+    ImportPng,
+    ImportGlyphs,
+    Diff,
+    Delta,
+    CombinePlanes,
+    Find,
+    Preview,
+    Query,
+    SelfTest,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Format {
+    #[default]
+    Verilog,
+    Memh,
+    BytePort,
+    RawHex,
+    Oneline,
+    Mif,
+    Coe,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl Format {
+    pub const ALL: &'static [Format] = &[
+        Self::Verilog,
+        Self::Memh,
+        Self::BytePort,
+        Self::RawHex,
+        Self::Oneline,
+        Self::Mif,
+        Self::Coe,
+        #[cfg(feature = "json")]
+        Self::Json,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Verilog => "verilog",
+            Self::Memh => "memh",
+            Self::BytePort => "byte-port",
+            Self::RawHex => "rawhex",
+            Self::Oneline => "oneline",
+            Self::Mif => "mif",
+            Self::Coe => "coe",
+            #[cfg(feature = "json")]
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Target {
+    #[default]
+    Generic,
+    Ice40,
+    Ecp5,
+}
+
+/// How the two 256-glyph pages of a PSF1_MODE512 font are read.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Psf1Pages {
+    /// One font of 512 glyphs
+    #[default]
+    Flat,
+    /// Two alternate 256-glyph fonts chosen by a page bit
+    Split,
+}
+
+/// The edge of clk registered outputs load on.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ClockEdge {
+    #[default]
+    Pos,
+    Neg,
+}
+
+impl ClockEdge {
+    /// The Verilog event keyword for the edge.
+    pub(crate) fn keyword(self) -> &'static str {
+        match self {
+            Self::Pos => "posedge",
+            Self::Neg => "negedge",
+        }
+    }
+}
+
+/// The assignment registered outputs use inside their clocked always blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AssignStyle {
+    Blocking,
+    #[default]
+    Nonblocking,
+}
+
+impl AssignStyle {
+    pub(crate) fn operator(self) -> &'static str {
+        match self {
+            Self::Blocking => "=",
+            Self::Nonblocking => "<=",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Lang {
+    #[default]
+    Verilog,
+    Vhdl,
+    Python,
+    Scala,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 4] = [Self::Verilog, Self::Vhdl, Self::Python, Self::Scala];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Verilog => "verilog",
+            Self::Vhdl => "vhdl",
+            Self::Python => "python",
+            Self::Scala => "scala",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum FoldCase {
+    Upper,
+    Lower,
+}
+
+impl FoldCase {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Upper => Self::Lower,
+            Self::Lower => Self::Upper,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Compression {
+    Rle,
+}
+
+#[derive(Debug)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
+#[derive(Debug, Default)]
+pub enum Radix {
+    #[default]
+    Hex,
+    Bin,
+    Dec,
+}
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(args: I) -> Result<Self, ParseError> {
+        let mut opts = Options::default();
+        let mut args = args.peekable();
+        let command = match args.peek().map(String::as_str) {
+            Some("stats") => Some(Command::Stats),
+            Some("export-glyphs") => Some(Command::ExportGlyphs),
+            Some("import-png") => Some(Command::ImportPng),
+            Some("import-glyphs") => Some(Command::ImportGlyphs),
+            Some("diff") => Some(Command::Diff),
+            Some("delta") => Some(Command::Delta),
+            Some("combine-planes") => Some(Command::CombinePlanes),
+            Some("find") => Some(Command::Find),
+            Some("preview") => Some(Command::Preview),
+            Some("query") => Some(Command::Query),
+            Some("selftest") => Some(Command::SelfTest),
+            _ => None,
+        };
+        if let Some(command) = command {
+            opts.command = command;
+            args.next();
+        }
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--with-enable" => opts.with_enable = true,
+                "--with-reset" => opts.with_reset = true,
+                "--with-valid" => opts.with_valid = true,
+                "--reset-active" => {
+                    opts.reset_active_low = match Self::value(&mut args, &arg)?.as_str() {
+                        "high" => false,
+                        "low" => true,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--reset-sync" => opts.reset_async = false,
+                "--reset-async" => opts.reset_async = true,
+                "--format" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    opts.format = *Format::ALL
+                        .iter()
+                        .find(|format| format.name() == value)
+                        .ok_or(ParseError::InvalidArgument(arg))?;
+                }
+                "--lang" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    opts.lang = *Lang::ALL
+                        .iter()
+                        .find(|lang| lang.name() == value)
+                        .ok_or(ParseError::InvalidArgument(arg))?;
+                }
+                "--target" => {
+                    opts.target = match Self::value(&mut args, &arg)?.as_str() {
+                        "generic" => Target::Generic,
+                        "ice40" => Target::Ice40,
+                        "ecp5" => Target::Ecp5,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--rom-style" => {
+                    opts.rom_style = match Self::value(&mut args, &arg)?.as_str() {
+                        "block" => Some("block"),
+                        "distributed" => Some("distributed"),
+                        "auto" => Some("auto"),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--compress" => {
+                    opts.compress = match Self::value(&mut args, &arg)?.as_str() {
+                        "rle" => Some(Compression::Rle),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--list-formats" => opts.list_formats = true,
+                "--list-langs" => opts.list_langs = true,
+                "--vhdl-bitstring" => opts.vhdl_bitstring = true,
+                "--init-file" => opts.init_file = Some(Self::value(&mut args, &arg)?),
+                "--no-wrapper" => opts.no_wrapper = true,
+                "--embed-table-comment" => opts.embed_table_comment = true,
+                "--group-bytes" => match Self::number(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    bytes => opts.group_bytes = Some(bytes),
+                },
+                "--group-sep" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    let mut chars = value.chars();
+                    opts.group_sep = match (chars.next(), chars.next()) {
+                        (Some(sep), None) if !sep.is_ascii_hexdigit() => Some(sep),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    };
+                }
+                "--package" => {
+                    let name = Self::value(&mut args, &arg)?;
+                    crate::vhdl::check_identifier(&name)?;
+                    opts.package = Some(name);
+                }
+                "--radix" => {
+                    opts.radix = match Self::value(&mut args, &arg)?.as_str() {
+                        "hex" => Radix::Hex,
+                        "bin" => Radix::Bin,
+                        "dec" => Radix::Dec,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--hex-case" => {
+                    opts.hex_lowercase = match Self::value(&mut args, &arg)?.as_str() {
+                        "upper" => false,
+                        "lower" => true,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--word-endian" => {
+                    opts.word_endian = match Self::value(&mut args, &arg)?.as_str() {
+                        "big" => Endian::Big,
+                        "little" => Endian::Little,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--check" => opts.check = true,
+                "--check-padding" => opts.check_padding = true,
+                "--check-codepage" => {
+                    opts.check_codepage = match Self::value(&mut args, &arg)?.as_str() {
+                        "cp437" => Some(Codepage::Cp437),
+                        "latin1" => Some(Codepage::Latin1),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--offset" => opts.offset = Self::number(&mut args, &arg)? as u64,
+                "--with-parity" => {
+                    opts.parity = match Self::value(&mut args, &arg)?.as_str() {
+                        "even" => Some(Parity::Even),
+                        "odd" => Some(Parity::Odd),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--psf1-count" => opts.psf1_count = Some(Self::number(&mut args, &arg)?),
+                "--subset-from" => opts.subset_from = Some(Self::value(&mut args, &arg)?),
+                "--codepage" => opts.codepage_map = Some(Self::value(&mut args, &arg)?),
+                "--out-dir" => opts.out_dir = Some(Self::value(&mut args, &arg)?),
+                "--cell-width" | "--width" => opts.cell_width = Self::number_u32(&mut args, &arg)?,
+                "--cell-height" | "--height" => {
+                    opts.cell_height = Self::number_u32(&mut args, &arg)?
+                }
+                "--collapse" => opts.collapse = true,
+                "--nibble-mode" => opts.nibble_mode = true,
+                "--no-default-arm" => opts.no_default_arm = true,
+                "--pipeline" => match Self::number(&mut args, &arg)? {
+                    stages @ 1..=2 => opts.pipeline = Some(stages),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--invert-address" => opts.invert_address = true,
+                "--reverse-address-bits" => opts.reverse_address_bits = true,
+                "--emit-selectable" => opts.emit_selectable = true,
+                "--signed-output" => opts.signed_output = true,
+                "--emit-grid-json" => opts.emit_grid_json = true,
+                "--emit-rom-file" => opts.emit_rom_file = Some(Self::value(&mut args, &arg)?),
+                "--emit-density" => opts.emit_density = true,
+                "--as-rom" => opts.as_rom = true,
+                "--emit-present" => opts.emit_present = true,
+                "--emit-hashed" => opts.emit_hashed = true,
+                "--emit-char-row" => opts.emit_char_row = true,
+                "--strip-control-chars" => opts.strip_control_chars = true,
+                "--validate-roundtrip" => opts.validate_roundtrip = true,
+                "--present-width" => match Self::number(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    width => opts.present_width = Some(width),
+                },
+                "--index" => {
+                    opts.grid_indices = Self::value(&mut args, &arg)?
+                        .split(',')
+                        .map(|index| index.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| ParseError::InvalidArgument(arg))?;
+                }
+                "--max-output-bits" => opts.max_output_bits = Some(Self::number(&mut args, &arg)?),
+                "--wrap-into" => opts.wrap_into = Some(Self::value(&mut args, &arg)?),
+                "--base-address" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    let base = match value
+                        .strip_prefix("0x")
+                        .or_else(|| value.strip_prefix("0X"))
+                    {
+                        Some(hex) => usize::from_str_radix(hex, 16),
+                        None => value.parse(),
+                    };
+                    opts.base_address = Some(base.map_err(|_| ParseError::InvalidArgument(arg))?);
+                }
+                "--blank-value" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    let byte = match value
+                        .strip_prefix("0x")
+                        .or_else(|| value.strip_prefix("0X"))
+                    {
+                        Some(hex) => u8::from_str_radix(hex, 16),
+                        None => value.parse(),
+                    };
+                    opts.blank_value = Some(byte.map_err(|_| ParseError::InvalidArgument(arg))?);
+                }
+                "--comment" => opts.comment = true,
+                "--named-glyphs" => opts.named_glyphs = true,
+                "--emit-muxtree" => opts.emit_muxtree = true,
+                "--emit-column-scan" => opts.emit_column_scan = true,
+                "--emit-initial-array" | "--emit-plain-init" => opts.emit_initial_array = true,
+                "--emit-lookup" => opts.emit_lookup = true,
+                "--emit-sorted-index" => opts.emit_sorted_index = true,
+                "--fold-case" => {
+                    opts.fold_case = match Self::value(&mut args, &arg)?.as_str() {
+                        "upper" => Some(FoldCase::Upper),
+                        "lower" => Some(FoldCase::Lower),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--emit-bbox" => opts.emit_bbox = true,
+                "--tb-vcd" => opts.tb_vcd = true,
+                "--normalize" => opts.normalize = true,
+                "--blanks-as-dontcare" => opts.blanks_as_dontcare = true,
+                "--shadow" => {
+                    opts.shadow = Some(match Self::value(&mut args, &arg)?.as_str() {
+                        "n" => (0, -1),
+                        "ne" => (1, -1),
+                        "e" => (1, 0),
+                        "se" => (1, 1),
+                        "s" => (0, 1),
+                        "sw" => (-1, 1),
+                        "w" => (-1, 0),
+                        "nw" => (-1, -1),
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    })
+                }
+                "--shadow-plane" => opts.shadow_plane = true,
+                "--rotate" => match Self::value(&mut args, &arg)?.as_str() {
+                    "180" => opts.rotate_180 = true,
+                    angle => {
+                        return Err(ParseError::InvalidArgument(format!(
+                            "--rotate {}: only 180 is supported",
+                            angle
+                        )))
+                    }
+                },
+                "--emit-header" => opts.emit_header = true,
+                "--emit-attr-rom" => opts.emit_attr_rom = true,
+                "--emit-struct" => opts.emit_struct = true,
+                "--attr-file" => opts.attr_file = Some(Self::value(&mut args, &arg)?),
+                "--attr-width" => match Self::number(&mut args, &arg)? {
+                    width @ 1..=64 => opts.attr_width = Some(width),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--split-512" => opts.split_512 = true,
+                "--psf1-pages" => {
+                    opts.psf1_pages = match Self::value(&mut args, &arg)?.as_str() {
+                        "flat" => Psf1Pages::Flat,
+                        "split" => Psf1Pages::Split,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--clock-edge" => {
+                    opts.clock_edge = match Self::value(&mut args, &arg)?.as_str() {
+                        "pos" => ClockEdge::Pos,
+                        "neg" => ClockEdge::Neg,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--assign-style" => {
+                    opts.assign_style = match Self::value(&mut args, &arg)?.as_str() {
+                        "blocking" => AssignStyle::Blocking,
+                        "nonblocking" => AssignStyle::Nonblocking,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--split-files" => match Self::number(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    files => opts.split_files = Some(files),
+                },
+                "--psf1-table-stride" => match Self::number(&mut args, &arg)? {
+                    stride if stride >= 2 => opts.psf1_table_stride = Some(stride),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--report-size" => opts.report_size = true,
+                "--strip-table" => opts.strip_table = true,
+                "--compact-table" => opts.compact_table = true,
+                "--bitplanes" => match Self::number_u32(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    planes => opts.bitplanes = Some(planes),
+                },
+                "--add-identity-table" => opts.add_identity_table = true,
+                "--trim-width" => opts.trim_width = true,
+                "--sort-by-codepoint" => opts.sort_by_codepoint = true,
+                "--drop-blanks" => opts.drop_blanks = true,
+                "--trim-trailing-blanks" => opts.trim_trailing_blanks = true,
+                "--output" => opts.output = Some(Self::value(&mut args, &arg)?),
+                "--max-width" => opts.max_width = Some(Self::number_u32(&mut args, &arg)?),
+                "--max-height" => opts.max_height = Some(Self::number_u32(&mut args, &arg)?),
+                "--pad-to" => opts.pad_to = Some(Self::number_u32(&mut args, &arg)?),
+                "--quiet" => opts.quiet = true,
+                "--trace" => opts.trace = true,
+                "--dual-port" => opts.dual_port = true,
+                #[cfg(feature = "json")]
+                "--from-json" => opts.from_json = true,
+                "--pad-width-to-byte" => opts.trim_padding_bits = false,
+                "--trim-padding-bits" => opts.trim_padding_bits = true,
+                "--bit-order" => {
+                    opts.lsb_first = match Self::value(&mut args, &arg)?.as_str() {
+                        "msb" => false,
+                        "lsb" => true,
+                        _ => return Err(ParseError::InvalidArgument(arg)),
+                    }
+                }
+                "--bank-width" => match Self::number(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    bank_width => opts.bank_width = Some(bank_width),
+                },
+                "--output-width" => opts.output_width = Some(Self::number(&mut args, &arg)?),
+                "--align-glyphs" => match Self::number(&mut args, &arg)? {
+                    align if align.is_power_of_two() => opts.align_glyphs = Some(align),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--addr-shift" => match Self::number(&mut args, &arg)? {
+                    shift @ 0..=31 => opts.addr_shift = Some(shift as u32),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--show-glyphs" => opts.show_glyphs = true,
+                "--pbm" => opts.pattern_path = Some(Self::value(&mut args, &arg)?),
+                "--tolerance" => opts.tolerance = Self::number(&mut args, &arg)?,
+                "--preview-png" => opts.preview_png = Some(Self::value(&mut args, &arg)?),
+                "--char" => {
+                    opts.query_char = Self::character(&Self::value(&mut args, &arg)?);
+                    if opts.query_char.is_none() {
+                        return Err(ParseError::InvalidArgument(arg));
+                    }
+                }
+                "--range" => {
+                    let value = Self::value(&mut args, &arg)?;
+                    opts.range = match value.split_once('-') {
+                        Some((start, end)) => Self::character(start).zip(Self::character(end)),
+                        None => None,
+                    }
+                    .filter(|(start, end)| start <= end);
+                    if opts.range.is_none() {
+                        return Err(ParseError::InvalidArgument(arg));
+                    }
+                }
+                "--preview-svg" => opts.preview_svg = Some(Self::value(&mut args, &arg)?),
+                "--preview-scale" => match Self::value(&mut args, &arg)?.parse::<f64>() {
+                    Ok(scale) if scale > 0.0 && scale <= 1.0 => opts.preview_scale = Some(scale),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                "--preview-cols" => match Self::number(&mut args, &arg)? {
+                    0 => return Err(ParseError::InvalidArgument(arg)),
+                    columns => opts.preview_cols = Some(columns),
+                },
+                _ if arg.starts_with("--") || opts.other_path.is_some() => {
+                    return Err(ParseError::InvalidArgument(arg));
+                }
+                _ if opts.path.is_some() => match opts.command {
+                    Command::Diff | Command::Delta | Command::CombinePlanes => {
+                        opts.other_path = Some(arg)
+                    }
+                    Command::Convert => opts.select_paths.push(arg),
+                    _ => return Err(ParseError::InvalidArgument(arg)),
+                },
+                _ => opts.path = Some(arg),
+            }
+        }
+        match opts.select_paths.first() {
+            Some(path) if !opts.emit_selectable => Err(ParseError::InvalidArgument(path.clone())),
+            _ => Ok(opts),
+        }
+    }
+
+    fn value<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<String, ParseError> {
+        args.next()
+            .ok_or_else(|| ParseError::MissingValue(flag.to_string()))
+    }
+
+    fn number<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<usize, ParseError> {
+        Self::value(args, flag)?
+            .parse()
+            .map_err(|_| ParseError::InvalidArgument(flag.to_string()))
+    }
+
+    fn number_u32<I: Iterator<Item = String>>(args: &mut I, flag: &str) -> Result<u32, ParseError> {
+        u32::try_from(Self::number(args, flag)?)
+            .map_err(|_| ParseError::InvalidArgument(flag.to_string()))
+    }
+
+    /// Reads a character given literally or as U+XXXX.
+    fn character(value: &str) -> Option<char> {
+        let hex = value
+            .strip_prefix("U+")
+            .or_else(|| value.strip_prefix("u+"));
+        let mut chars = value.chars();
+        match (hex, chars.next(), chars.next()) {
+            (Some(hex), _, _) if !hex.is_empty() => {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            }
+            (_, Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// Whether the glyphs are emitted as two 256-glyph halves behind a page input.
+    pub(crate) fn split_pages(&self) -> bool {
+        self.split_512 || self.psf1_pages == Psf1Pages::Split
+    }
+
+    pub(crate) fn registered(&self) -> bool {
+        self.with_enable
+            || self.with_reset
+            || self.with_valid
+            || self.pipeline.is_some()
+            || self.target != Target::Generic
+    }
+}
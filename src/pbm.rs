@@ -0,0 +1,192 @@
+use super::{ParseError, TableEntry, Version, PSF};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+impl PSF {
+    pub fn write_pbm<W: Write>(&self, glyph: usize, out: &mut W) -> io::Result<()> {
+        writeln!(out, "P1")?;
+        writeln!(out, "{} {}", self.width, self.height)?;
+        let glyph = self.glyph(glyph);
+        for row in 0..glyph.height() {
+            let pixels: Vec<&str> = glyph
+                .row_bits(row)
+                .map(|set| if set { "1" } else { "0" })
+                .collect();
+            writeln!(out, "{}", pixels.join(" "))?;
+        }
+        Ok(())
+    }
+
+    pub fn export_glyphs(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut mapping = BufWriter::new(File::create(dir.join("mapping.txt"))?);
+        let mut used = BTreeSet::new();
+        for glyph in 0..self.glyph_count as usize {
+            let represented = self
+                .table
+                .as_ref()
+                .and_then(|table| table.get(glyph))
+                .map_or(&[][..], |entry| &entry.represented[..]);
+            let name = match represented.first() {
+                Some(&c) if !used.contains(&c) => {
+                    used.insert(c);
+                    format!("U+{:04X}.pbm", c as u32)
+                }
+                _ => format!("glyph{:04}.pbm", glyph),
+            };
+            let mut pbm = BufWriter::new(File::create(dir.join(&name))?);
+            self.write_pbm(glyph, &mut pbm)?;
+            pbm.flush()?;
+            write!(mapping, "{} {}", glyph, name)?;
+            for c in represented {
+                write!(mapping, " U+{:04X}", *c as u32)?;
+            }
+            writeln!(mapping)?;
+        }
+        mapping.flush()
+    }
+
+    /// Builds a font from the U+XXXX.pbm files in `dir`, one glyph per file in code point
+    /// order, each drawing its file's code point in the unicode table. Other files are
+    /// ignored. Every image must be `width`x`height`, or when either is 0 the size of the
+    /// first image.
+    pub fn import_glyphs(dir: &Path, width: u32, height: u32) -> Result<Self, ParseError> {
+        let mut files = BTreeMap::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let c = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("U+")?.strip_suffix(".pbm"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .and_then(char::from_u32);
+            if let Some(c) = c {
+                files.insert(c, path);
+            }
+        }
+        let mut size = (width as usize, height as usize);
+        let mut images = vec![];
+        for (&c, path) in &files {
+            let (image_width, image_height, pixels) = parse_pbm(&fs::read(path)?)?;
+            if images.is_empty() && (size.0 == 0 || size.1 == 0) {
+                size = (image_width, image_height);
+            }
+            if (image_width, image_height) != size {
+                return Err(ParseError::InvalidArgument(format!(
+                    "{} is {}x{}, not {}x{}",
+                    path.display(),
+                    image_width,
+                    image_height,
+                    size.0,
+                    size.1
+                )));
+            }
+            images.push((c, pixels));
+        }
+        if images.is_empty() {
+            return Err(ParseError::InvalidArgument(format!(
+                "{} holds no U+XXXX.pbm glyphs",
+                dir.display()
+            )));
+        }
+        let (width, height) = size;
+        let stride = width.div_ceil(8);
+        let charsize = stride * height;
+        Self::checked_geometry(width as u32, height as u32, charsize as u32)?;
+        let mut bitmap = vec![0u8; charsize * images.len()];
+        for (glyph, (_, pixels)) in images.iter().enumerate() {
+            for (n, _) in pixels.iter().enumerate().filter(|&(_, &set)| set) {
+                let (row, col) = (n / width, n % width);
+                bitmap[glyph * charsize + row * stride + col / 8] |= 0x80 >> (col % 8);
+            }
+        }
+        Ok(PSF {
+            version: Version::PSF2,
+            glyph_count: images.len() as u32,
+            charsize: charsize as u32,
+            height: height as u32,
+            width: width as u32,
+            bitmap,
+            table: Some(
+                images
+                    .iter()
+                    .map(|&(c, _)| TableEntry {
+                        represented: vec![c],
+                        sequences: vec![],
+                    })
+                    .collect(),
+            ),
+            has_sequences: false,
+        })
+    }
+}
+
+/// Reads a plain (P1) or raw (P4) PBM image as its width, height and pixels row by row, set
+/// meaning black.
+pub fn parse_pbm(bytes: &[u8]) -> Result<(usize, usize, Vec<bool>), ParseError> {
+    let malformed = |reason: &str| ParseError::MalformedPBM(reason.to_string());
+    let mut pos = 0;
+    let mut header = vec![];
+    while header.len() < 3 {
+        match bytes.get(pos) {
+            Some(b'#') => {
+                while bytes.get(pos).is_some_and(|&byte| byte != b'\n') {
+                    pos += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => pos += 1,
+            Some(_) => {
+                let start = pos;
+                while bytes
+                    .get(pos)
+                    .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+                {
+                    pos += 1;
+                }
+                header.push(String::from_utf8_lossy(&bytes[start..pos]).into_owned());
+            }
+            None => return Err(malformed("truncated header")),
+        }
+    }
+    let dimension = |field: &str| {
+        field
+            .parse::<usize>()
+            .map_err(|_| malformed(&format!("bad dimension {}", field)))
+    };
+    let (width, height) = (dimension(&header[1])?, dimension(&header[2])?);
+    if width == 0 || height == 0 {
+        return Err(malformed(&format!(
+            "{}x{} image has no pixels",
+            width, height
+        )));
+    }
+    // Every pixel takes at least a byte of P1 text or a bit of P4 data, so larger sizes
+    // cannot be in the file.
+    let count = width
+        .checked_mul(height)
+        .filter(|&count| count / 8 <= bytes.len())
+        .ok_or_else(|| malformed("truncated pixels"))?;
+    let pixels: Vec<bool> = match header[0].as_str() {
+        "P1" => bytes[pos..]
+            .iter()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .map(|&byte| byte == b'1')
+            .take(count)
+            .collect(),
+        "P4" => {
+            let stride = width.div_ceil(8);
+            let data = bytes.get(pos + 1..).unwrap_or_default();
+            data.chunks_exact(stride)
+                .take(height)
+                .flat_map(|row| (0..width).map(move |col| row[col / 8] & (0x80 >> (col % 8)) != 0))
+                .collect()
+        }
+        magic => return Err(malformed(&format!("unsupported magic {}", magic))),
+    };
+    if pixels.len() != count {
+        return Err(malformed("truncated pixels"));
+    }
+    Ok((width, height, pixels))
+}
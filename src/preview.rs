@@ -0,0 +1,124 @@
+use super::PSF;
+use std::io::{self, Write};
+
+impl PSF {
+    /// Draws every glyph with '#' for set pixels, `columns` glyphs side by side per row of
+    /// the grid, each headed by its index.
+    pub fn write_preview<W: Write>(&self, columns: usize, out: &mut W) -> io::Result<()> {
+        let width = self.width as usize;
+        let indices: Vec<usize> = (0..self.glyph_count as usize).collect();
+        for (n, row_of_glyphs) in indices.chunks(columns).enumerate() {
+            if n != 0 {
+                writeln!(out)?;
+            }
+            let labels: Vec<String> = row_of_glyphs
+                .iter()
+                .map(|index| format!("{:<width$}", index, width = width))
+                .collect();
+            writeln!(out, "{}", labels.join("  ").trim_end())?;
+            for row in 0..self.height as usize {
+                let cells: Vec<String> = row_of_glyphs
+                    .iter()
+                    .map(|&index| {
+                        self.glyph(index)
+                            .row_bits(row)
+                            .map(|set| if set { '#' } else { '.' })
+                            .collect()
+                    })
+                    .collect();
+                writeln!(out, "{}", cells.join("  "))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the pixels of the glyphs at `indices` as JSON, each a 2D array of 0 and 1 with
+    /// a nested array per row, top row first; a single index gives its grid alone, several
+    /// an array of grids in the order given.
+    pub fn write_grid_json<W: Write>(&self, indices: &[usize], out: &mut W) -> io::Result<()> {
+        let grid = |index: usize, indent: &str| -> String {
+            let glyph = self.glyph(index);
+            let rows: Vec<String> = (0..glyph.height())
+                .map(|row| {
+                    let bits: Vec<&str> = glyph
+                        .row_bits(row)
+                        .map(|set| if set { "1" } else { "0" })
+                        .collect();
+                    format!("{}  [{}]", indent, bits.join(","))
+                })
+                .collect();
+            format!("{}[\n{}\n{}]", indent, rows.join(",\n"), indent)
+        };
+        match indices {
+            [index] => writeln!(out, "{}", grid(*index, "")),
+            _ => {
+                let grids: Vec<String> = indices.iter().map(|&index| grid(index, "  ")).collect();
+                writeln!(out, "[\n{}\n]", grids.join(",\n"))
+            }
+        }
+    }
+
+    /// Writes every glyph as an SVG sheet `columns` glyphs wide, one unit per pixel: a
+    /// `<rect>` per set pixel inside an outlined cell, headed by the glyph's first code
+    /// point from the unicode table, or its index when it has none.
+    pub fn write_preview_svg<W: Write>(&self, columns: usize, out: &mut W) -> io::Result<()> {
+        const LABEL: usize = 6;
+        const GAP: usize = 2;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (cell_width, cell_height) = (width + GAP, height + LABEL + GAP);
+        let rows = (self.glyph_count as usize).div_ceil(columns);
+        let (sheet_width, sheet_height) = (columns * cell_width + GAP, rows * cell_height + GAP);
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = sheet_width,
+            h = sheet_height
+        )?;
+        writeln!(
+            out,
+            "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>",
+            sheet_width, sheet_height
+        )?;
+        for index in 0..self.glyph_count as usize {
+            let left = GAP + (index % columns) * cell_width;
+            let top = GAP + (index / columns) * cell_height;
+            let label = self
+                .table
+                .as_ref()
+                .and_then(|table| table.get(index))
+                .and_then(|entry| entry.represented.first())
+                .map(|&c| format!("U+{:04X}", c as u32))
+                .unwrap_or_else(|| index.to_string());
+            writeln!(out, "<g id=\"glyph{}\">", index)?;
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"4\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\">{}</text>",
+                left,
+                top + LABEL - 2,
+                width.min(label.len() * 3),
+                label
+            )?;
+            writeln!(
+                out,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#ccc\" stroke-width=\"0.1\"/>",
+                left,
+                top + LABEL,
+                width,
+                height
+            )?;
+            let glyph = self.glyph(index);
+            for row in 0..height {
+                for col in (0..width).filter(|&col| glyph.pixel(row, col)) {
+                    writeln!(
+                        out,
+                        "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\"/>",
+                        left + col,
+                        top + LABEL + row
+                    )?;
+                }
+            }
+            writeln!(out, "</g>")?;
+        }
+        writeln!(out, "</svg>")
+    }
+}
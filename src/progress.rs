@@ -0,0 +1,39 @@
+/// A progress display on stderr, shown only when built with the progress feature, not
+/// silenced with `--quiet` and stderr is a terminal.
+pub struct Progress {
+    #[cfg(feature = "progress")]
+    bar: Option<indicatif::ProgressBar>,
+}
+
+impl Progress {
+    #[cfg(feature = "progress")]
+    pub fn new(quiet: bool) -> Self {
+        use std::io::IsTerminal;
+        let bar = (!quiet && std::io::stderr().is_terminal()).then(|| {
+            let bar = indicatif::ProgressBar::new_spinner();
+            bar.enable_steady_tick(std::time::Duration::from_millis(100));
+            bar
+        });
+        Self { bar }
+    }
+
+    #[cfg(not(feature = "progress"))]
+    pub fn new(_quiet: bool) -> Self {
+        Self {}
+    }
+
+    /// Shows what is being worked on, e.g. "writing 512 glyphs".
+    pub fn stage(&self, _message: String) {
+        #[cfg(feature = "progress")]
+        if let Some(bar) = &self.bar {
+            bar.set_message(_message);
+        }
+    }
+
+    pub fn finish(&self, _message: String) {
+        #[cfg(feature = "progress")]
+        if let Some(bar) = &self.bar {
+            bar.finish_with_message(_message);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use super::{Options, PSF};
+use crate::verilog::hex_digits;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+impl PSF {
+    /// Writes a Python module holding the glyph words in FONT, ready for an Amaranth or Migen
+    /// `Memory(init=FONT)`, with the geometry and, when the font has one, the unicode table.
+    pub fn write_python<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        writeln!(out, "WIDTH = {}", self.width)?;
+        writeln!(out, "HEIGHT = {}", self.height)?;
+        writeln!(out, "COUNT = {}", words.len())?;
+        writeln!(out, "WORD_WIDTH = {}", words.first().map_or(0, Vec::len))?;
+        writeln!(out)?;
+        writeln!(out, "FONT = [")?;
+        for word in &words {
+            writeln!(out, "    0x{},", hex_digits(word, opts))?;
+        }
+        writeln!(out, "]")?;
+        if let Some(table) = &self.table {
+            let mut glyphs = BTreeMap::new();
+            for (index, entry) in table.iter().enumerate() {
+                for &c in &entry.represented {
+                    glyphs.entry(c).or_insert(index);
+                }
+            }
+            writeln!(out)?;
+            writeln!(out, "# Code point to glyph index")?;
+            writeln!(out, "UNICODE = {{")?;
+            for (c, index) in glyphs {
+                writeln!(out, "    0x{:04X}: {},", c as u32, index)?;
+            }
+            writeln!(out, "}}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Options, BACKENDS, PSF};
+use std::io::{self, Write};
+
+/// Counts the bytes written to it.
+#[derive(Default)]
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl PSF {
+    /// Writes a table comparing, for every backend with the given options, the bytes it would
+    /// emit (memh counting its hex file too), the comparison arms of the glyph ROM and the
+    /// bits the ROM stores. Nothing is written to disk.
+    pub fn write_size_report<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        let rom_bits = words.len() * words.first().map_or(0, Vec::len);
+        let runs = 1 + words.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        writeln!(
+            out,
+            "{:<20} {:>12} {:>8} {:>12}",
+            "backend", "bytes", "arms", "rom bits"
+        )?;
+        for backend in BACKENDS {
+            let mut counter = Counter::default();
+            match backend.name() {
+                "memh" => {
+                    let mut hex = Counter::default();
+                    self.write_memh_parts(opts, &mut counter, &mut hex)?;
+                    counter.0 += hex.0;
+                }
+                "mif" if !opts.no_wrapper => {
+                    let mut mif = Counter::default();
+                    self.write_mif_parts(opts, &mut counter, &mut mif)?;
+                    counter.0 += mif.0;
+                }
+                _ => backend.emit(self, opts, &mut counter)?,
+            }
+            let arms = match backend.name() {
+                "verilog" if opts.collapse => (runs + 1).to_string(),
+                "verilog" => (words.len() + 1).to_string(),
+                "byte-port" => (self.glyph_count as usize * self.charsize as usize + 1).to_string(),
+                _ => "-".to_string(),
+            };
+            let bits = match backend.name() {
+                "byte-port" => self.glyph_count as usize * self.charsize as usize * 8,
+                _ => rom_bits,
+            };
+            writeln!(
+                out,
+                "{:<20} {:>12} {:>8} {:>12}",
+                backend.name(),
+                counter.0,
+                arms,
+                bits
+            )?;
+        }
+        if !opts.collapse {
+            writeln!(
+                out,
+                "{:<20} {:>12} {:>8} {:>12}",
+                "verilog --collapse",
+                "-",
+                runs + 1,
+                rom_bits
+            )?;
+        }
+        Ok(())
+    }
+}
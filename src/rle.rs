@@ -0,0 +1,145 @@
+use super::notes::{note, Note};
+use super::verilog::{address_width, write_rom_module};
+use super::{ClockEdge, Options};
+use std::io::{self, Write};
+
+const MAX_RUN: usize = 128;
+
+/// Encodes bits as one byte per run: the run's bit value in bit 7 and its length minus one
+/// in bits 6-0, so a run covers 1 to 128 bits.
+pub fn rle_encode(bits: &[bool]) -> Vec<u8> {
+    let mut symbols = vec![];
+    let mut rest = bits;
+    while let Some(&value) = rest.first() {
+        let run = rest
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|&&bit| bit == value)
+            .count();
+        symbols.push((value as u8) << 7 | (run - 1) as u8);
+        rest = &rest[run..];
+    }
+    symbols
+}
+
+/// Expands symbols written by `rle_encode` back into bits, as the decoder module does.
+pub fn rle_decode(symbols: &[u8]) -> Vec<bool> {
+    symbols
+        .iter()
+        .flat_map(|&symbol| std::iter::repeat_n(symbol & 0x80 != 0, (symbol & 0x7f) as usize + 1))
+        .collect()
+}
+
+fn to_bits(value: usize, width: usize) -> Vec<bool> {
+    (0..width)
+        .rev()
+        .map(|bit| value & (1 << bit) != 0)
+        .collect()
+}
+
+pub(crate) fn write_rle<W: Write>(
+    out: &mut W,
+    words: &[Vec<bool>],
+    clock_edge: ClockEdge,
+) -> io::Result<()> {
+    let output_width = words.first().map_or(0, Vec::len);
+    let mut offsets = vec![];
+    let mut symbols = vec![];
+    for word in words {
+        offsets.push(symbols.len());
+        symbols.extend(rle_encode(word));
+    }
+    note(Note::Compressed {
+        bytes: words.len() * output_width.div_ceil(8),
+        runs: symbols.len(),
+    });
+    let input_width = address_width(words.len()).max(1);
+    let offset_width = address_width(symbols.len()).max(1);
+    let count_width = address_width(output_width).max(1);
+    let rom_opts = Options::default();
+
+    let offset_words: Vec<Vec<bool>> = offsets
+        .iter()
+        .map(|&offset| to_bits(offset, offset_width))
+        .collect();
+    write_rom_module(
+        &rom_opts,
+        out,
+        "glyphmap_rle_index",
+        "codepoint",
+        "offset",
+        &offset_words,
+        &[],
+    )?;
+    writeln!(out)?;
+    let symbol_words: Vec<Vec<bool>> = symbols
+        .iter()
+        .map(|&symbol| to_bits(symbol as usize, 8))
+        .collect();
+    write_rom_module(
+        &rom_opts,
+        out,
+        "glyphmap_rle_data",
+        "address",
+        "symbol",
+        &symbol_words,
+        &[],
+    )?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "module glyphmap ( input wire clk, input wire start, input wire [{}:0] codepoint, \
+         output reg [{}:0] glyph, output reg done );",
+        input_width - 1,
+        output_width - 1
+    )?;
+    writeln!(out, "wire [{}:0] offset;", offset_width - 1)?;
+    writeln!(out, "wire [7:0] symbol;")?;
+    writeln!(out, "reg [{}:0] pointer;", offset_width - 1)?;
+    writeln!(out, "reg [6:0] position;")?;
+    writeln!(out, "reg [{}:0] produced;", count_width - 1)?;
+    writeln!(out, "reg busy;")?;
+    writeln!(
+        out,
+        "glyphmap_rle_index index ( .codepoint(codepoint), .offset(offset) );"
+    )?;
+    writeln!(
+        out,
+        "glyphmap_rle_data data ( .address(pointer), .symbol(symbol) );"
+    )?;
+    writeln!(out, "always @({} clk)", clock_edge.keyword())?;
+    writeln!(out, "    if (start) begin")?;
+    writeln!(out, "        pointer <= offset;")?;
+    writeln!(out, "        position <= 0;")?;
+    writeln!(out, "        produced <= 0;")?;
+    writeln!(out, "        busy <= 1;")?;
+    writeln!(out, "        done <= 0;")?;
+    writeln!(out, "    end else if (busy) begin")?;
+    if output_width == 1 {
+        writeln!(out, "        glyph <= symbol[7];")?;
+    } else {
+        writeln!(
+            out,
+            "        glyph <= {{glyph[{}:0], symbol[7]}};",
+            output_width - 2
+        )?;
+    }
+    writeln!(out, "        if (position == symbol[6:0]) begin")?;
+    writeln!(out, "            pointer <= pointer + 1;")?;
+    writeln!(out, "            position <= 0;")?;
+    writeln!(out, "        end else")?;
+    writeln!(out, "            position <= position + 1;")?;
+    writeln!(
+        out,
+        "        if (produced == {}'d{}) begin",
+        count_width,
+        output_width - 1
+    )?;
+    writeln!(out, "            busy <= 0;")?;
+    writeln!(out, "            done <= 1;")?;
+    writeln!(out, "        end else")?;
+    writeln!(out, "            produced <= produced + 1;")?;
+    writeln!(out, "    end")?;
+    writeln!(out, "endmodule")
+}
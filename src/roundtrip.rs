@@ -0,0 +1,130 @@
+use super::verilog::hex_digits;
+use super::{backend, Options, PSF};
+
+/// The backends whose output `--validate-roundtrip` can read back.
+pub(crate) const ROUNDTRIP_BACKENDS: &[&str] = &[
+    "rawhex",
+    "oneline",
+    "python",
+    "scala",
+    #[cfg(feature = "json")]
+    "json",
+];
+
+impl PSF {
+    /// Reads back `output`, as written by the backend `--lang` and `--format` select, and
+    /// checks it against the font: the hex words against the words the glyphs pack to, and
+    /// the json backend's font, parsed again, against every glyph and the unicode table.
+    /// Returns the first difference found.
+    pub fn validate_roundtrip(&self, opts: &Options, output: &[u8]) -> Result<(), String> {
+        let text = String::from_utf8_lossy(output);
+        let read: Vec<&str> = match backend(opts).name() {
+            "rawhex" => text
+                .lines()
+                .filter_map(|line| line.split_once(": "))
+                .map(|(_, word)| word)
+                .collect(),
+            "oneline" => text
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .map(|(_, word)| word)
+                .collect(),
+            "python" => text
+                .lines()
+                .skip_while(|&line| line != "FONT = [")
+                .skip(1)
+                .take_while(|&line| line != "]")
+                .filter_map(|line| line.trim().strip_prefix("0x"))
+                .map(|word| word.trim_end_matches(','))
+                .collect(),
+            "scala" => text
+                .lines()
+                .filter_map(|line| line.trim().strip_prefix("BigInt(\""))
+                .filter_map(|word| word.split_once('"'))
+                .map(|(word, _)| word)
+                .collect(),
+            #[cfg(feature = "json")]
+            "json" => return self.compare_json(output),
+            name => return Err(format!("cannot read back {} output", name)),
+        };
+        let words = self.glyph_words(opts);
+        if read.len() != words.len() {
+            return Err(format!(
+                "read back {} words for {} glyphs",
+                read.len(),
+                words.len()
+            ));
+        }
+        for (index, (digits, word)) in read.iter().zip(&words).enumerate() {
+            let bits: Option<Vec<bool>> = digits
+                .chars()
+                .filter(|&c| Some(c) != opts.group_sep && c != '_')
+                .map(|c| c.to_digit(16))
+                .collect::<Option<Vec<u32>>>()
+                .map(|nibbles| {
+                    nibbles
+                        .iter()
+                        .flat_map(|nibble| (0..4).rev().map(move |bit| nibble >> bit & 1 != 0))
+                        .collect()
+                });
+            let matches = bits.as_ref().is_some_and(|bits| {
+                bits.len().checked_sub(word.len()).is_some_and(|padding| {
+                    bits[..padding].iter().all(|&bit| !bit) && bits[padding..] == word[..]
+                })
+            });
+            if !matches {
+                return Err(format!(
+                    "glyph {} reads back as {} instead of {}",
+                    index,
+                    digits,
+                    hex_digits(word, opts)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    fn compare_json(&self, output: &[u8]) -> Result<(), String> {
+        let read = PSF::from_json(output).map_err(|err| format!("{:?}", err))?;
+        if (read.width, read.height, read.glyph_count)
+            != (self.width, self.height, self.glyph_count)
+        {
+            return Err(format!(
+                "read back {} {}x{} glyphs for {} {}x{}",
+                read.glyph_count,
+                read.width,
+                read.height,
+                self.glyph_count,
+                self.width,
+                self.height
+            ));
+        }
+        let pixel_bytes = self.pixel_bytes();
+        let glyphs = |psf: &PSF| {
+            psf.bitmap
+                .chunks_exact(psf.charsize as usize)
+                .map(|glyph| glyph[..pixel_bytes].to_vec())
+                .collect::<Vec<_>>()
+        };
+        if let Some(index) = glyphs(self)
+            .iter()
+            .zip(glyphs(&read).iter())
+            .position(|(glyph, read)| glyph != read)
+        {
+            return Err(format!("glyph {} reads back differently", index));
+        }
+        let entries = |psf: &PSF| {
+            psf.table.as_ref().map(|table| {
+                table
+                    .iter()
+                    .map(|entry| (entry.represented.clone(), entry.sequences.clone()))
+                    .collect::<Vec<_>>()
+            })
+        };
+        if entries(self) != entries(&read) {
+            return Err("the unicode table reads back differently".to_string());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use super::{Options, PSF};
+use crate::verilog::hex_digits;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+impl PSF {
+    /// Writes a Scala object Font holding the glyph words in `font`, ready for a Chisel
+    /// `VecInit(Font.font.map(_.U(Font.WordWidth.W)))`, with the geometry and, when the font
+    /// has one, the unicode table.
+    pub fn write_scala<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        writeln!(out, "object Font {{")?;
+        writeln!(out, "  val Width = {}", self.width)?;
+        writeln!(out, "  val Height = {}", self.height)?;
+        writeln!(out, "  val Count = {}", words.len())?;
+        writeln!(
+            out,
+            "  val WordWidth = {}",
+            words.first().map_or(0, Vec::len)
+        )?;
+        writeln!(out)?;
+        let words: Vec<String> = words
+            .iter()
+            .map(|word| format!("    BigInt(\"{}\", 16)", hex_digits(word, opts)))
+            .collect();
+        writeln!(out, "  val font: Seq[BigInt] = Seq(")?;
+        writeln!(out, "{}", words.join(",\n"))?;
+        writeln!(out, "  )")?;
+        if let Some(table) = &self.table {
+            let mut glyphs = BTreeMap::new();
+            for (index, entry) in table.iter().enumerate() {
+                for &c in &entry.represented {
+                    glyphs.entry(c).or_insert(index);
+                }
+            }
+            let glyphs: Vec<String> = glyphs
+                .into_iter()
+                .map(|(c, index)| format!("    0x{:04X} -> {}", c as u32, index))
+                .collect();
+            writeln!(out)?;
+            writeln!(out, "  // Code point to glyph index")?;
+            writeln!(out, "  val unicode: Map[Int, Int] = Map(")?;
+            writeln!(out, "{}", glyphs.join(",\n"))?;
+            writeln!(out, "  )")?;
+        }
+        writeln!(out, "}}")
+    }
+}
@@ -0,0 +1,101 @@
+use super::{Options, PSF};
+use std::env;
+use std::fs;
+use std::io::{self, Cursor, Write};
+use std::panic::{self, AssertUnwindSafe};
+
+const GLYPHS: u32 = 4;
+const SIZE: u32 = 8;
+
+/// A PSF2 font of `GLYPHS` 8x8 diagonal glyphs drawing 'A', 'B', ... in turn.
+fn font_bytes() -> (Vec<u8>, Vec<u8>) {
+    let bitmap: Vec<u8> = (0..GLYPHS * SIZE)
+        .map(|i| 0x80 >> ((i / SIZE + i % SIZE) % 8))
+        .collect();
+    let mut bytes = vec![0x72, 0xb5, 0x4a, 0x86];
+    for field in [0, 32, 0x01, GLYPHS, SIZE, SIZE, SIZE] {
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    bytes.extend_from_slice(&bitmap);
+    for c in (b'A'..).take(GLYPHS as usize) {
+        bytes.extend_from_slice(&[c, 0xFF]);
+    }
+    (bytes, bitmap)
+}
+
+impl PSF {
+    /// Parses a font built in memory and runs it through every backend, writing a line per
+    /// check, and returns whether all of them passed.
+    pub fn selftest<W: Write>(out: &mut W) -> io::Result<bool> {
+        let mut passed = true;
+        let mut check = |name: &str, result: Result<(), String>| {
+            passed &= result.is_ok();
+            match result {
+                Ok(()) => writeln!(out, "ok      {}", name),
+                Err(reason) => writeln!(out, "FAILED  {}: {}", name, reason),
+            }
+        };
+
+        let (bytes, bitmap) = font_bytes();
+        let psf = match PSF::parse_reader(Cursor::new(bytes)) {
+            Ok(psf) => psf,
+            Err(err) => {
+                check("parse", Err(format!("{:?}", err)))?;
+                return Ok(false);
+            }
+        };
+        let geometry = psf.geometry();
+        check(
+            "parse",
+            if (geometry.width, geometry.height, geometry.glyph_count) != (SIZE, SIZE, GLYPHS) {
+                Err(format!(
+                    "read {}x{} with {} glyphs",
+                    geometry.width, geometry.height, geometry.glyph_count
+                ))
+            } else if psf.bitmap != bitmap {
+                Err("bitmap differs".to_string())
+            } else if psf.table.as_ref().map_or(0, Vec::len) != GLYPHS as usize {
+                Err("unicode table missing".to_string())
+            } else {
+                Ok(())
+            },
+        )?;
+
+        let init_file =
+            env::temp_dir().join(format!("psf2verilog-selftest-{}.hex", std::process::id()));
+        let opts = Options {
+            init_file: Some(init_file.to_string_lossy().into_owned()),
+            ..Options::default()
+        };
+        for backend in super::BACKENDS {
+            let mut output = vec![];
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| backend.emit(&psf, &opts, &mut output)));
+            check(
+                backend.name(),
+                match result {
+                    Err(_) => Err("panicked".to_string()),
+                    Ok(Err(err)) => Err(err.to_string()),
+                    Ok(Ok(())) if output.is_empty() => Err("wrote nothing".to_string()),
+                    Ok(Ok(())) => Ok(()),
+                },
+            )?;
+        }
+        let _ = fs::remove_file(init_file);
+
+        let mut output = vec![];
+        psf.write_rom(&opts, &mut output)?;
+        let arms = String::from_utf8_lossy(&output)
+            .matches("codepoint ==")
+            .count();
+        check(
+            "verilog arms",
+            if arms == GLYPHS as usize {
+                Ok(())
+            } else {
+                Err(format!("{} arms for {} glyphs", arms, GLYPHS))
+            },
+        )?;
+        Ok(passed)
+    }
+}
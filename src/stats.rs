@@ -0,0 +1,86 @@
+use super::verilog::write_rom_module;
+use super::{Options, PSF};
+use std::io::{self, Write};
+
+impl PSF {
+    pub fn write_stats<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let counts: Vec<usize> = (0..self.glyph_count as usize)
+            .map(|glyph| self.set_pixels(glyph))
+            .collect();
+        let total: usize = counts.iter().sum();
+        let blank = counts.iter().filter(|&&count| count == 0).count();
+        writeln!(out, "version             {}", self.version)?;
+        writeln!(out, "glyphs              {}", counts.len())?;
+        writeln!(
+            out,
+            "sequences           {}",
+            if self.has_sequences { "yes" } else { "no" }
+        )?;
+        writeln!(out, "blank glyphs        {}", blank)?;
+        writeln!(out, "set pixels          {}", total)?;
+        writeln!(
+            out,
+            "average per glyph   {:.2}",
+            total as f64 / counts.len().max(1) as f64
+        )?;
+        let densest = counts.iter().enumerate().max_by_key(|&(_, count)| count);
+        let sparsest = counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count != 0)
+            .min_by_key(|&(_, count)| count);
+        for (label, glyph) in [("densest glyph", densest), ("sparsest non-blank", sparsest)] {
+            match glyph {
+                Some((index, count)) => {
+                    writeln!(out, "{:<19} {} ({} pixels)", label, index, count)?
+                }
+                None => writeln!(out, "{:<19} -", label)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the set-pixel count of every glyph, a line of index and count per glyph, or
+    /// with `--as-rom` a glyphdensity ROM of the counts, wide enough for a full glyph.
+    pub fn write_density<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let counts = (0..self.glyph_count as usize).map(|glyph| self.set_pixels(glyph));
+        if !opts.as_rom {
+            for (index, count) in counts.enumerate() {
+                writeln!(out, "{} {}", index, count)?;
+            }
+            return Ok(());
+        }
+        let width = (u32::BITS - (self.width * self.height).leading_zeros()).max(1);
+        let words: Vec<Vec<bool>> = counts
+            .map(|count| (0..width).rev().map(|bit| count >> bit & 1 != 0).collect())
+            .collect();
+        write_rom_module(
+            opts,
+            out,
+            "glyphdensity",
+            "codepoint",
+            "density",
+            &words,
+            &[],
+        )
+    }
+
+    /// Writes a glyphpresent ROM of one bit per glyph, set if the glyph has any pixel set,
+    /// packed `--present-width` glyphs to a word with the lowest glyph in bit 0.
+    pub fn write_present<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let width = opts.present_width.unwrap_or(32);
+        let present: Vec<bool> = (0..self.glyph_count as usize)
+            .map(|glyph| self.set_pixels(glyph) != 0)
+            .collect();
+        let words: Vec<Vec<bool>> = present
+            .chunks(width)
+            .map(|bits| {
+                (0..width)
+                    .rev()
+                    .map(|bit| bits.get(bit).copied().unwrap_or(false))
+                    .collect()
+            })
+            .collect();
+        write_rom_module(opts, out, "glyphpresent", "word", "present", &words, &[])
+    }
+}
@@ -0,0 +1,374 @@
+use super::{unimap, Bounds, ParseError, TableEntry, Version, PSF};
+use std::collections::{BTreeMap, BTreeSet};
+
+impl PSF {
+    fn repack<F: Fn(usize, usize, usize) -> bool>(&mut self, width: u32, height: u32, pixel: F) {
+        let stride = (width as usize).div_ceil(8);
+        let charsize = stride * height as usize;
+        let mut bitmap = vec![0u8; charsize * self.glyph_count as usize];
+        for glyph in 0..self.glyph_count as usize {
+            for row in 0..height as usize {
+                for col in 0..width as usize {
+                    if pixel(glyph, row, col) {
+                        bitmap[glyph * charsize + row * stride + col / 8] |= 0x80 >> (col % 8);
+                    }
+                }
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.charsize = charsize as u32;
+        self.bitmap = bitmap;
+    }
+
+    pub fn trim_width(&mut self) {
+        let bounds: Vec<Bounds> = (0..self.glyph_count as usize)
+            .filter_map(|glyph| self.glyph(glyph).bounds())
+            .collect();
+        let left = bounds.iter().map(|b| b.x_min).min();
+        let right = bounds.iter().map(|b| b.x_max).max();
+        if let (Some(left), Some(right)) = (left, right) {
+            let original = self.clone();
+            self.repack((right - left + 1) as u32, self.height, |glyph, row, col| {
+                original.glyph(glyph).pixel(row, col + left)
+            });
+        }
+    }
+
+    /// Moves every glyph so its tight bounding box sits in the middle of the cell, any odd
+    /// spare pixel going right and below. Blank glyphs are left as they are.
+    pub fn normalize(&mut self) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let bounds: Vec<Option<Bounds>> = (0..self.glyph_count as usize)
+            .map(|glyph| self.glyph(glyph).bounds())
+            .collect();
+        let original = self.clone();
+        self.repack(self.width, self.height, |glyph, row, col| {
+            match &bounds[glyph] {
+                Some(b) => {
+                    let left = (width - (b.x_max - b.x_min + 1)) / 2;
+                    let top = (height - (b.y_max - b.y_min + 1)) / 2;
+                    (row + b.y_min)
+                        .checked_sub(top)
+                        .zip((col + b.x_min).checked_sub(left))
+                        .is_some_and(|(row, col)| {
+                            row < height && col < width && original.glyph(glyph).pixel(row, col)
+                        })
+                }
+                None => false,
+            }
+        });
+    }
+
+    /// Adds a copy of every glyph moved one pixel by `(dx, dy)`, ORed into the glyph or, with
+    /// `separate_plane`, as a second bit plane so each pixel becomes the pair {shadow, glyph}
+    /// and the width doubles. Shadow pixels moved past the cell edge are dropped.
+    pub fn shadow(&mut self, (dx, dy): (isize, isize), separate_plane: bool) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let original = self.clone();
+        let shadow = |glyph: usize, row: usize, col: usize| {
+            row.checked_add_signed(-dy)
+                .zip(col.checked_add_signed(-dx))
+                .is_some_and(|(row, col)| {
+                    row < height && col < width && original.glyph(glyph).pixel(row, col)
+                })
+        };
+        if separate_plane {
+            self.repack(self.width * 2, self.height, |glyph, row, col| {
+                if col % 2 == 0 {
+                    shadow(glyph, row, col / 2)
+                } else {
+                    original.glyph(glyph).pixel(row, col / 2)
+                }
+            });
+        } else {
+            self.repack(self.width, self.height, |glyph, row, col| {
+                original.glyph(glyph).pixel(row, col) || shadow(glyph, row, col)
+            });
+        }
+    }
+
+    /// Turns every glyph upside down. Unlike the other transforms this works on the row bytes
+    /// rather than pixel by pixel: the rows are taken in reverse order, each row's bytes
+    /// reversed and the bits of each byte reversed, and the row then shifted left by its
+    /// padding bits, which the reversal leaves at the front. The geometry is unchanged.
+    pub fn rotate_180(&mut self) {
+        let stride = (self.width as usize).div_ceil(8);
+        let padding = (stride * 8 - self.width as usize) as u32;
+        let charsize = self.charsize as usize;
+        let height = self.height as usize;
+        for glyph in self.bitmap.chunks_exact_mut(charsize) {
+            let original = glyph[..stride * height].to_vec();
+            for (rows, row) in glyph
+                .chunks_exact_mut(stride)
+                .zip(original.chunks_exact(stride).rev())
+            {
+                for (byte, source) in rows.iter_mut().zip(row.iter().rev()) {
+                    *byte = source.reverse_bits();
+                }
+                if padding > 0 {
+                    for i in 0..stride {
+                        let next = rows.get(i + 1).map_or(0, |next| next >> (8 - padding));
+                        rows[i] = rows[i] << padding | next;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Interleaves `other` into this font as a second bit plane, so each pixel becomes the
+    /// pair {other, self} and the width doubles.
+    pub fn combine_planes(&mut self, other: &PSF) -> Result<(), ParseError> {
+        if (self.width, self.height, self.glyph_count)
+            != (other.width, other.height, other.glyph_count)
+        {
+            return Err(ParseError::InvalidArgument(format!(
+                "combine-planes needs fonts of the same geometry, got {} {}x{} glyphs and {} {}x{} glyphs",
+                self.glyph_count, self.width, self.height, other.glyph_count, other.width, other.height
+            )));
+        }
+        let original = self.clone();
+        self.repack(self.width * 2, self.height, |glyph, row, col| {
+            let plane = if col % 2 == 0 { other } else { &original };
+            plane.glyph(glyph).pixel(row, col / 2)
+        });
+        Ok(())
+    }
+
+    /// Repeats every pixel `planes` times across the row, as that many identical bit planes.
+    pub fn replicate_planes(&mut self, planes: u32) {
+        let original = self.clone();
+        self.repack(self.width * planes, self.height, |glyph, row, col| {
+            original.glyph(glyph).pixel(row, col / planes as usize)
+        });
+    }
+
+    /// Drops the C0 control characters from every table entry, the stray carriage returns
+    /// and line feeds a table picks up when the font goes through a text-mode editor, and
+    /// returns how many were dropped. The entry separators are bytes of their own, not
+    /// characters, so they are untouched.
+    pub fn strip_control_chars(&mut self) -> usize {
+        let mut stripped = 0;
+        for entry in self.table.iter_mut().flatten() {
+            for chars in [&mut entry.represented, &mut entry.sequences] {
+                let before = chars.len();
+                chars.retain(|c| *c >= ' ');
+                stripped += before - chars.len();
+            }
+        }
+        if self.version != Version::PSF1 {
+            self.has_sequences = Self::sequences_in(&self.table);
+        }
+        stripped
+    }
+
+    pub fn sort_by_codepoint(&mut self) -> Result<(), ParseError> {
+        let table = self.table.as_ref().ok_or_else(|| {
+            ParseError::InvalidArgument("--sort-by-codepoint needs a unicode table".to_string())
+        })?;
+        let mut order: Vec<usize> = (0..self.glyph_count as usize).collect();
+        order.sort_by_key(|&glyph| {
+            match table.get(glyph).and_then(|entry| entry.represented.first()) {
+                Some(&c) => (false, c),
+                None => (true, '\0'),
+            }
+        });
+        self.select(&order);
+        Ok(())
+    }
+
+    pub fn subset(&mut self, codepoints: &BTreeSet<char>) -> Result<(), ParseError> {
+        let table = self.table.as_ref().ok_or_else(|| {
+            ParseError::InvalidArgument("--subset-from needs a unicode table".to_string())
+        })?;
+        let keep: Vec<usize> = (0..self.glyph_count as usize)
+            .filter(|&glyph| {
+                table
+                    .get(glyph)
+                    .is_some_and(|entry| entry.represented.iter().any(|c| codepoints.contains(c)))
+            })
+            .collect();
+        if keep.is_empty() {
+            return Err(ParseError::InvalidArgument(
+                "--subset-from matches no glyph in the font".to_string(),
+            ));
+        }
+        self.select(&keep);
+        Ok(())
+    }
+
+    /// Removes the glyphs without a set pixel after the last one with any, returning how many
+    /// went.
+    pub fn trim_trailing_blanks(&mut self) -> Result<usize, ParseError> {
+        let keep = (0..self.glyph_count as usize)
+            .rposition(|glyph| self.set_pixels(glyph) != 0)
+            .ok_or_else(|| {
+                ParseError::InvalidArgument(
+                    "--trim-trailing-blanks would drop every glyph".to_string(),
+                )
+            })?
+            + 1;
+        let trimmed = self.glyph_count as usize - keep;
+        self.select(&(0..keep).collect::<Vec<_>>());
+        Ok(trimmed)
+    }
+
+    /// Removes every glyph without a set pixel, returning each original glyph's new index, or
+    /// None for the dropped ones.
+    pub fn drop_blanks(&mut self) -> Result<Vec<Option<usize>>, ParseError> {
+        let keep: Vec<usize> = (0..self.glyph_count as usize)
+            .filter(|&glyph| self.set_pixels(glyph) != 0)
+            .collect();
+        if keep.is_empty() {
+            return Err(ParseError::InvalidArgument(
+                "--drop-blanks would drop every glyph".to_string(),
+            ));
+        }
+        let mut remap = vec![None; self.glyph_count as usize];
+        for (packed, &glyph) in keep.iter().enumerate() {
+            remap[glyph] = Some(packed);
+        }
+        self.select(&keep);
+        Ok(remap)
+    }
+
+    /// Rebuilds the font in the order of a code page: glyph K is the glyph the unicode table
+    /// gives the code point `map` assigns to K. Indices the map skips and code points the
+    /// font lacks are left blank; the latter are returned.
+    pub fn retarget(
+        &mut self,
+        map: &BTreeMap<usize, char>,
+    ) -> Result<Vec<(usize, char)>, ParseError> {
+        let table = self.table.as_ref().ok_or_else(|| {
+            ParseError::InvalidArgument("--codepage needs a unicode table".to_string())
+        })?;
+        let charsize = self.charsize as usize;
+        let length = map.keys().next_back().map_or(0, |&last| last + 1);
+        let mut bitmap = vec![0u8; charsize * length];
+        let mut entries = vec![TableEntry::default(); length];
+        let mut missing = vec![];
+        for (&index, &c) in map {
+            match table
+                .iter()
+                .position(|entry| entry.represented.contains(&c))
+            {
+                Some(glyph) => {
+                    bitmap[index * charsize..][..charsize]
+                        .copy_from_slice(&self.bitmap[glyph * charsize..][..charsize]);
+                    entries[index].represented.push(c);
+                }
+                None => missing.push((index, c)),
+            }
+        }
+        self.bitmap = bitmap;
+        self.table = Some(entries);
+        self.glyph_count = length as u32;
+        Ok(missing)
+    }
+
+    /// Rebuilds the font from the glyphs the unicode table gives `start..=end`, glyph K
+    /// drawing `start + K`. Code points the font lacks are left blank and returned.
+    pub fn retarget_range(
+        &mut self,
+        start: char,
+        end: char,
+    ) -> Result<Vec<(usize, char)>, ParseError> {
+        if self.table.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--range needs a unicode table".to_string(),
+            ));
+        }
+        let map = (start..=end)
+            .map(|c| (c as usize - start as usize, c))
+            .collect();
+        self.retarget(&map)
+    }
+
+    /// Appends glyphs of every byte `fill` until the font has `count` glyphs, their table
+    /// entries empty.
+    pub fn pad_to(&mut self, count: u32, fill: u8) -> Result<(), ParseError> {
+        if count < self.glyph_count {
+            return Err(ParseError::InvalidArgument(format!(
+                "--pad-to {} is less than the font's {} glyphs",
+                count, self.glyph_count
+            )));
+        }
+        self.bitmap
+            .resize(count as usize * self.charsize as usize, fill);
+        if let Some(table) = &mut self.table {
+            table.resize(count as usize, TableEntry::default());
+        }
+        self.glyph_count = count;
+        Ok(())
+    }
+
+    /// Rebuilds the font from the listed glyphs, in that order.
+    fn select(&mut self, glyphs: &[usize]) {
+        let charsize = self.charsize as usize;
+        self.bitmap = glyphs
+            .iter()
+            .flat_map(|&glyph| self.bitmap[glyph * charsize..][..charsize].to_vec())
+            .collect();
+        if let Some(table) = &self.table {
+            self.table = Some(
+                glyphs
+                    .iter()
+                    .map(|&glyph| table.get(glyph).cloned().unwrap_or_default())
+                    .collect(),
+            );
+        }
+        self.glyph_count = glyphs.len() as u32;
+    }
+}
+
+/// Reads a `--codepage` map, one "INDEX U+XXXX" line per index, with the index in decimal,
+/// 0x hex or 0 octal as in a unimap and # starting a comment.
+pub fn parse_codepage_map(text: &str) -> Result<BTreeMap<usize, char>, ParseError> {
+    let mut map = BTreeMap::new();
+    for (n, line) in text.lines().enumerate() {
+        let fields: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let malformed =
+            || ParseError::InvalidArgument(format!("--codepage line {}: {:?}", n + 1, line));
+        let (index, c) = match fields.as_slice() {
+            [] => continue,
+            [index, c] => (
+                unimap::position(index).ok_or_else(malformed)? as usize,
+                unimap::codepoint(c)
+                    .and_then(char::from_u32)
+                    .ok_or_else(malformed)?,
+            ),
+            _ => return Err(malformed()),
+        };
+        if map.insert(index, c).is_some() {
+            return Err(ParseError::InvalidArgument(format!(
+                "--codepage line {}: index {} is mapped twice",
+                n + 1,
+                index
+            )));
+        }
+    }
+    if map.is_empty() {
+        return Err(ParseError::InvalidArgument(
+            "--codepage map is empty".to_string(),
+        ));
+    }
+    Ok(map)
+}
+
+/// Reads the code points of a UTF-8 strings file, ignoring a leading byte order mark and
+/// control characters such as line breaks.
+pub fn subset_codepoints(bytes: &[u8]) -> Result<BTreeSet<char>, ParseError> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let text = std::str::from_utf8(bytes).map_err(|err| {
+        ParseError::InvalidArgument(format!(
+            "--subset-from file is not valid UTF-8 after byte {}",
+            err.valid_up_to()
+        ))
+    })?;
+    Ok(text.chars().filter(|c| !c.is_control()).collect())
+}
@@ -0,0 +1,79 @@
+use super::TableEntry;
+use std::ops::RangeInclusive;
+
+pub(super) fn parse(text: &str, glyph_count: usize) -> Option<Vec<TableEntry>> {
+    let mut table = vec![TableEntry::default(); glyph_count];
+    let mut recognized = false;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let positions = match fields.next() {
+            Some(field) => range(field, position)?,
+            None => continue,
+        };
+        if *positions.end() as usize >= glyph_count {
+            return None;
+        }
+        let rest: Vec<&str> = fields.collect();
+        if rest == ["idem"] {
+            for position in positions {
+                table[position as usize]
+                    .represented
+                    .push(char::from_u32(position)?);
+            }
+        } else if rest.is_empty() {
+            return None;
+        } else {
+            for field in rest {
+                let codepoints = range(field, codepoint)?;
+                if positions.start() == positions.end() {
+                    for codepoint in codepoints {
+                        table[*positions.start() as usize]
+                            .represented
+                            .push(char::from_u32(codepoint)?);
+                    }
+                } else if codepoints.end() - codepoints.start()
+                    == positions.end() - positions.start()
+                {
+                    for (position, codepoint) in positions.clone().zip(codepoints) {
+                        table[position as usize]
+                            .represented
+                            .push(char::from_u32(codepoint)?);
+                    }
+                } else {
+                    return None;
+                }
+            }
+        }
+        recognized = true;
+    }
+    Some(table).filter(|_| recognized)
+}
+
+fn range(field: &str, parse: fn(&str) -> Option<u32>) -> Option<RangeInclusive<u32>> {
+    let (start, end) = match field.split_once('-') {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => (parse(field)?, parse(field)?),
+    };
+    Some(start..=end).filter(|range| !range.is_empty())
+}
+
+pub(super) fn position(field: &str) -> Option<u32> {
+    if let Some(hex) = field
+        .strip_prefix("0x")
+        .or_else(|| field.strip_prefix("0X"))
+    {
+        u32::from_str_radix(hex, 16).ok()
+    } else if field.len() > 1 && field.starts_with('0') {
+        u32::from_str_radix(&field[1..], 8).ok()
+    } else {
+        field.parse().ok()
+    }
+}
+
+pub(super) fn codepoint(field: &str) -> Option<u32> {
+    let hex = field
+        .strip_prefix("U+")
+        .or_else(|| field.strip_prefix("u+"))?;
+    u32::from_str_radix(hex, 16).ok()
+}
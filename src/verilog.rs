@@ -0,0 +1,2305 @@
+use super::notes::{note, Note};
+use super::roundtrip::ROUNDTRIP_BACKENDS;
+use super::{
+    backend, rle::write_rle, Bounds, Command, Compression, Endian, FoldCase, Format, Lang, Options,
+    Parity, ParseError, Psf1Pages, Radix, Target, Version, PSF,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const MEMH_FILE: &str = "charmap.hex";
+const MIF_FILE: &str = "charmap.mif";
+const HEADER_FILE: &str = "glyphmap.vh";
+
+/// What the output says about one glyph: its `--comment` text, `--named-glyphs` name and
+/// whether `--blanks-as-dontcare` leaves its value to the synthesizer.
+pub(crate) struct Label {
+    comment: String,
+    name: Option<String>,
+    dontcare: bool,
+}
+
+impl PSF {
+    pub fn check_options(&self, opts: &Options) -> Result<(), ParseError> {
+        if self.glyph_count == 0 {
+            return Err(ParseError::InvalidArgument(
+                "the font has no glyphs to convert".to_string(),
+            ));
+        }
+        if let Some(max) = opts.max_width.filter(|&max| self.width > max) {
+            return Err(ParseError::InvalidArgument(format!(
+                "the glyphs are {} pixels wide, more than --max-width {}; crop empty columns \
+                 with --trim-width or convert a smaller size of the font",
+                self.width, max
+            )));
+        }
+        if let Some(max) = opts.max_height.filter(|&max| self.height > max) {
+            return Err(ParseError::InvalidArgument(format!(
+                "the glyphs are {} pixels high, more than --max-height {}; convert a smaller \
+                 size of the font",
+                self.height, max
+            )));
+        }
+        if opts.psf1_pages == Psf1Pages::Split
+            && (self.version != Version::PSF1 || self.glyph_count != 512)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--psf1-pages split needs a 512-glyph PSF1 font".to_string(),
+            ));
+        }
+        if opts.split_pages() && self.glyph_count != 512 {
+            return Err(ParseError::InvalidArgument(format!(
+                "--split-512 needs a 512-glyph font, this one has {}",
+                self.glyph_count
+            )));
+        }
+        if opts.trim_padding_bits
+            && (opts.format == Format::BytePort
+                || opts.lsb_first
+                || matches!(opts.word_endian, Endian::Little))
+        {
+            return Err(ParseError::InvalidArgument(
+                "--trim-padding-bits cannot be combined with --format byte-port, \
+                 --bit-order lsb or --word-endian little"
+                    .to_string(),
+            ));
+        }
+        let glyph_bits = if opts.trim_padding_bits {
+            (self.width * self.height) as usize
+        } else {
+            self.pixel_bytes() * 8
+        };
+        match opts.output_width {
+            Some(width) if width < glyph_bits => {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--output-width {} is narrower than the {}-bit glyphs",
+                    width, glyph_bits
+                )))
+            }
+            _ => {}
+        }
+        if opts.as_rom && !opts.emit_density {
+            return Err(ParseError::InvalidArgument(
+                "--as-rom needs --emit-density".to_string(),
+            ));
+        }
+        if opts.validate_roundtrip {
+            let name = backend(opts).name();
+            if !ROUNDTRIP_BACKENDS.contains(&name) {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--validate-roundtrip cannot read back {} output, only {}",
+                    name,
+                    ROUNDTRIP_BACKENDS.join(", ")
+                )));
+            }
+            if opts.emit_selectable
+                || opts.emit_grid_json
+                || opts.emit_density
+                || opts.emit_present
+                || opts.emit_hashed
+                || opts.emit_char_row
+                || opts.emit_column_scan
+                || opts.wrap_into.is_some()
+            {
+                return Err(ParseError::InvalidArgument(
+                    "--validate-roundtrip cannot be combined with --emit-selectable, \
+                     --emit-grid-json, --emit-density, --emit-present, --emit-hashed, \
+                     --emit-char-row, --emit-column-scan or --wrap-into"
+                        .to_string(),
+                ));
+            }
+        }
+        if (opts.emit_char_row || opts.emit_column_scan)
+            && (opts.invert_address
+                || opts.reverse_address_bits
+                || opts.base_address.is_some()
+                || opts.lsb_first
+                || matches!(opts.word_endian, Endian::Little))
+        {
+            return Err(ParseError::InvalidArgument(
+                "--emit-char-row and --emit-column-scan cannot be combined with \
+                 --invert-address, --reverse-address-bits, --base-address, --bit-order lsb or --word-endian little"
+                    .to_string(),
+            ));
+        }
+        if opts.emit_hashed {
+            if self.table.is_none() {
+                return Err(ParseError::InvalidArgument(
+                    "--emit-hashed needs a unicode table".to_string(),
+                ));
+            }
+            if opts.registered()
+                || opts.invert_address
+                || opts.reverse_address_bits
+                || opts.base_address.is_some()
+            {
+                return Err(ParseError::InvalidArgument(
+                    "--emit-hashed cannot be combined with a registered output, \
+                     --invert-address, --reverse-address-bits or --base-address"
+                        .to_string(),
+                ));
+            }
+        }
+        if opts.present_width.is_some() && !opts.emit_present {
+            return Err(ParseError::InvalidArgument(
+                "--present-width needs --emit-present".to_string(),
+            ));
+        }
+        let modes: Vec<&str> = [
+            (opts.emit_selectable, "--emit-selectable"),
+            (opts.emit_grid_json, "--emit-grid-json"),
+            (opts.emit_density, "--emit-density"),
+            (opts.emit_present, "--emit-present"),
+            (opts.emit_hashed, "--emit-hashed"),
+            (opts.emit_char_row, "--emit-char-row"),
+            (opts.emit_column_scan, "--emit-column-scan"),
+        ]
+        .iter()
+        .filter(|&&(given, _)| given)
+        .map(|&(_, mode)| mode)
+        .collect();
+        if modes.len() > 1 {
+            return Err(ParseError::InvalidArgument(format!(
+                "{} cannot be combined, each writes its own module in place of the ROM",
+                modes.join(" and ")
+            )));
+        }
+        if let Command::Delta = opts.command {
+            let transforms: Vec<&str> = [
+                (opts.strip_control_chars, "--strip-control-chars"),
+                (opts.bitplanes.is_some(), "--bitplanes"),
+                (opts.add_identity_table, "--add-identity-table"),
+                (opts.normalize, "--normalize"),
+                (opts.shadow.is_some(), "--shadow"),
+                (opts.rotate_180, "--rotate"),
+                (opts.trim_width, "--trim-width"),
+                (opts.subset_from.is_some(), "--subset-from"),
+                (opts.sort_by_codepoint, "--sort-by-codepoint"),
+                (opts.codepage_map.is_some(), "--codepage"),
+                (opts.range.is_some(), "--range"),
+                (opts.trim_trailing_blanks, "--trim-trailing-blanks"),
+                (opts.drop_blanks, "--drop-blanks"),
+                (opts.pad_to.is_some(), "--pad-to"),
+            ]
+            .iter()
+            .filter(|&&(given, _)| given)
+            .map(|&(_, transform)| transform)
+            .collect();
+            if !transforms.is_empty() {
+                return Err(ParseError::InvalidArgument(format!(
+                    "delta cannot be combined with {}, which would change only the base font",
+                    transforms.join(", ")
+                )));
+            }
+        }
+        if opts.emit_grid_json == opts.grid_indices.is_empty() {
+            return Err(ParseError::InvalidArgument(
+                "--emit-grid-json and --index must be given together".to_string(),
+            ));
+        }
+        if let Some(index) = opts
+            .grid_indices
+            .iter()
+            .find(|&&index| index >= self.glyph_count as usize)
+        {
+            return Err(ParseError::InvalidArgument(format!(
+                "--index {} is past the last of the {} glyphs",
+                index, self.glyph_count
+            )));
+        }
+        if opts.range.is_some() && opts.codepage_map.is_some() {
+            return Err(ParseError::InvalidArgument(
+                "--range cannot be combined with --codepage".to_string(),
+            ));
+        }
+        if opts.split_files.is_some() && opts.split_pages() {
+            return Err(ParseError::InvalidArgument(
+                "--split-files cannot be combined with --split-512".to_string(),
+            ));
+        }
+        if opts.emit_lookup && self.table.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--emit-lookup needs a unicode table".to_string(),
+            ));
+        }
+        if opts.emit_sorted_index && self.table.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--emit-sorted-index needs a unicode table".to_string(),
+            ));
+        }
+        if opts.compress.is_some()
+            && (opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.bank_width.is_some()
+                || opts.invert_address
+                || opts.reverse_address_bits
+                || opts.with_reset
+                || opts.with_enable
+                || opts.signed_output)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--compress cannot be combined with --split-512, --split-files, --bank-width, \
+                 --invert-address, --reverse-address-bits, --with-reset, --with-enable or \
+                 --signed-output"
+                    .to_string(),
+            ));
+        }
+        if opts.dual_port
+            && (opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.bank_width.is_some()
+                || opts.compress.is_some()
+                || opts.emit_muxtree
+                || opts.with_enable
+                || opts.with_reset)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--dual-port cannot be combined with --split-512, --split-files, --bank-width, \
+                 --compress, --emit-muxtree, --with-enable or --with-reset"
+                    .to_string(),
+            ));
+        }
+        if opts.attr_file.is_some() && !opts.emit_attr_rom {
+            return Err(ParseError::InvalidArgument(
+                "--attr-file needs --emit-attr-rom".to_string(),
+            ));
+        }
+        if opts.emit_attr_rom {
+            self.attributes(opts)?;
+        }
+        if opts.fold_case.is_some() && !opts.emit_lookup {
+            return Err(ParseError::InvalidArgument(
+                "--fold-case needs --emit-lookup".to_string(),
+            ));
+        }
+        match opts.bank_width {
+            Some(width) if opts.blank_value.is_some() && !width.is_multiple_of(8) => {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--blank-value needs a --bank-width that is a multiple of 8, not {}",
+                    width
+                )))
+            }
+            _ => {}
+        }
+        if opts.pipeline == Some(2)
+            && (opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.compress.is_some()
+                || opts.dual_port)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--pipeline 2 cannot be combined with --split-512, --split-files, --compress \
+                 or --dual-port"
+                    .to_string(),
+            ));
+        }
+        if opts.with_valid
+            && (opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.bank_width.is_some()
+                || opts.compress.is_some()
+                || opts.dual_port
+                || opts.pipeline == Some(2))
+        {
+            return Err(ParseError::InvalidArgument(
+                "--with-valid cannot be combined with --split-512, --split-files, --bank-width, \
+                 --compress, --dual-port or --pipeline 2"
+                    .to_string(),
+            ));
+        }
+        if opts.reverse_address_bits
+            && (opts.collapse || opts.split_pages() || opts.split_files.is_some())
+        {
+            return Err(ParseError::InvalidArgument(
+                "--reverse-address-bits cannot be combined with --collapse, --split-512 or \
+                 --split-files"
+                    .to_string(),
+            ));
+        }
+        if opts.emit_rom_file.is_some() {
+            if opts.format != Format::Memh || opts.lang != Lang::Verilog {
+                return Err(ParseError::InvalidArgument(
+                    "--emit-rom-file needs --format memh".to_string(),
+                ));
+            }
+            if opts.init_file.is_some() || opts.wrap_into.is_some() || opts.emit_selectable {
+                return Err(ParseError::InvalidArgument(
+                    "--emit-rom-file cannot be combined with --init-file, --wrap-into or \
+                     --emit-selectable"
+                        .to_string(),
+                ));
+            }
+        }
+        if opts.tb_vcd
+            && (opts.format != Format::Verilog
+                || opts.lang != Lang::Verilog
+                || opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.compress.is_some()
+                || opts.dual_port)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--tb-vcd needs --format verilog and cannot be combined with --split-512, \
+                 --split-files, --compress or --dual-port"
+                    .to_string(),
+            ));
+        }
+        if opts.blanks_as_dontcare
+            && (opts.format != Format::Verilog
+                || opts.lang != Lang::Verilog
+                || opts.emit_muxtree
+                || opts.emit_initial_array
+                || opts.dual_port
+                || opts.compress.is_some())
+        {
+            return Err(ParseError::InvalidArgument(
+                "--blanks-as-dontcare needs --format verilog and cannot be combined with \
+                 --emit-muxtree, --emit-initial-array, --dual-port or --compress"
+                    .to_string(),
+            ));
+        }
+        if opts.embed_table_comment && self.table.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--embed-table-comment needs a unicode table".to_string(),
+            ));
+        }
+        if opts.group_sep.is_some() && opts.group_bytes.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--group-sep needs --group-bytes".to_string(),
+            ));
+        }
+        if opts.group_sep.is_some_and(|sep| sep != '_')
+            && !matches!(opts.format, Format::RawHex | Format::Oneline)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--group-sep other than _ needs --format rawhex or oneline, Verilog literals \
+                 only allow _"
+                    .to_string(),
+            ));
+        }
+        if opts.shadow_plane && opts.shadow.is_none() {
+            return Err(ParseError::InvalidArgument(
+                "--shadow-plane needs --shadow".to_string(),
+            ));
+        }
+        if let Some(shift) = opts.addr_shift {
+            if opts.format != Format::BytePort || opts.lang != Lang::Verilog {
+                return Err(ParseError::InvalidArgument(
+                    "--addr-shift needs --format byte-port".to_string(),
+                ));
+            }
+            if opts.align_glyphs.is_some() {
+                return Err(ParseError::InvalidArgument(
+                    "--addr-shift cannot be combined with --align-glyphs".to_string(),
+                ));
+            }
+            if (self.charsize as u64) > 1 << shift {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--addr-shift {} leaves {} bytes per glyph, fewer than the {}-byte glyphs",
+                    shift,
+                    1u64 << shift,
+                    self.charsize
+                )));
+            }
+            if (self.glyph_count as u64) << shift > 1 << 32 {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--addr-shift {} puts the {} glyphs past the 32-bit address space",
+                    shift, self.glyph_count
+                )));
+            }
+        }
+        if let Some(base) = opts.base_address {
+            if opts.format != Format::BytePort || opts.lang != Lang::Verilog {
+                return Err(ParseError::InvalidArgument(
+                    "--base-address needs --format byte-port".to_string(),
+                ));
+            }
+            if opts.emit_muxtree
+                || opts.emit_initial_array
+                || opts.invert_address
+                || opts.reverse_address_bits
+            {
+                return Err(ParseError::InvalidArgument(
+                    "--base-address cannot be combined with --emit-muxtree, \
+                     --emit-initial-array, --invert-address or --reverse-address-bits"
+                        .to_string(),
+                ));
+            }
+            let end = base as u64 + self.byte_port_depth(opts) as u64;
+            if end > 1 << 32 {
+                return Err(ParseError::InvalidArgument(format!(
+                    "--base-address {:#x} puts the {}-word ROM past the 32-bit address space",
+                    base,
+                    self.byte_port_depth(opts)
+                )));
+            }
+        }
+        if opts.emit_selectable
+            && (opts.split_pages()
+                || opts.split_files.is_some()
+                || opts.compress.is_some()
+                || opts.dual_port
+                || opts.with_valid
+                || opts.format != Format::Verilog
+                || opts.lang != Lang::Verilog)
+        {
+            return Err(ParseError::InvalidArgument(
+                "--emit-selectable needs --format verilog and cannot be combined with \
+                 --split-512, --split-files, --compress, --dual-port or --with-valid"
+                    .to_string(),
+            ));
+        }
+        if let Some(max) = opts.max_output_bits {
+            let width = match opts.format {
+                Format::BytePort => {
+                    (if opts.nibble_mode { 4 } else { 8 }) + opts.parity.is_some() as usize
+                }
+                _ => self.glyph_words(opts).first().map_or(0, Vec::len),
+            };
+            let width = opts
+                .bank_width
+                .map_or(width, |bank_width| bank_width.min(width));
+            if width > max {
+                return Err(ParseError::InvalidArgument(format!(
+                    "the {}-bit output is wider than --max-output-bits {}; split it into \
+                     narrower ROMs with --bank-width {}",
+                    width, max, max
+                )));
+            }
+        }
+        if opts.emit_initial_array && (opts.emit_muxtree || opts.collapse) {
+            return Err(ParseError::InvalidArgument(
+                "--emit-initial-array cannot be combined with --emit-muxtree or --collapse"
+                    .to_string(),
+            ));
+        }
+        if opts.named_glyphs
+            && (opts.emit_muxtree
+                || opts.emit_initial_array
+                || opts.dual_port
+                || opts.compress.is_some())
+        {
+            return Err(ParseError::InvalidArgument(
+                "--named-glyphs cannot be combined with --emit-muxtree, --emit-initial-array, \
+                 --dual-port or --compress"
+                    .to_string(),
+            ));
+        }
+        if opts.report_size && opts.split_files.is_some() {
+            return Err(ParseError::InvalidArgument(
+                "--report-size cannot be combined with --split-files".to_string(),
+            ));
+        }
+        if opts.emit_muxtree && opts.collapse {
+            return Err(ParseError::InvalidArgument(
+                "--collapse cannot be combined with --emit-muxtree".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn glyphs(&self) -> impl Iterator<Item = &[u8]> {
+        self.bitmap
+            .chunks_exact(self.charsize as usize)
+            .take(self.glyph_count as usize)
+    }
+
+    pub(crate) fn glyph_words(&self, opts: &Options) -> Vec<Vec<bool>> {
+        self.glyphs()
+            .enumerate()
+            .map(|(index, glyph)| {
+                let word = if opts.trim_padding_bits {
+                    let glyph = self.glyph(index);
+                    (0..glyph.height())
+                        .flat_map(|row| glyph.row_bits(row))
+                        .collect()
+                } else {
+                    glyph_word(&glyph[..self.pixel_bytes()], opts)
+                };
+                match opts.output_width {
+                    Some(width) if width > word.len() => {
+                        let mut padded = vec![false; width - word.len()];
+                        padded.extend(word);
+                        padded
+                    }
+                    _ => word,
+                }
+            })
+            .map(|word| with_parity(word, opts))
+            .collect()
+    }
+
+    fn glyph_labels(&self, opts: &Options) -> Vec<Label> {
+        if !opts.comment && !opts.named_glyphs && !opts.blanks_as_dontcare {
+            return vec![];
+        }
+        let mut used = HashSet::new();
+        (0..self.glyph_count as usize)
+            .map(|glyph| {
+                let represented = self
+                    .table
+                    .as_ref()
+                    .and_then(|table| table.get(glyph))
+                    .map_or(&[][..], |entry| &entry.represented[..]);
+                let comment = match opts.comment {
+                    true => represented
+                        .iter()
+                        .map(|&c| codepoint_label(c))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    false => String::new(),
+                };
+                let name = opts.named_glyphs.then(|| {
+                    let name = match represented.first() {
+                        Some(&c) => format!("GLYPH_{:02X}", c as u32),
+                        None => format!("GLYPH_INDEX_{}", glyph),
+                    };
+                    if used.insert(name.clone()) {
+                        name
+                    } else {
+                        format!("GLYPH_INDEX_{}", glyph)
+                    }
+                });
+                Label {
+                    comment,
+                    name,
+                    dontcare: opts.blanks_as_dontcare && self.set_pixels(glyph) == 0,
+                }
+            })
+            .collect()
+    }
+
+    fn unmapped_glyphs(&self) -> Vec<(usize, usize)> {
+        let mut runs: Vec<(usize, usize)> = vec![];
+        for (index, entry) in self.table.iter().flatten().enumerate() {
+            if entry.represented.is_empty() {
+                match runs.last_mut() {
+                    Some((_, last)) if *last + 1 == index => *last = index,
+                    _ => runs.push((index, index)),
+                }
+            }
+        }
+        runs
+    }
+
+    fn write_bbox<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let x_width = address_width(self.width as usize).max(1);
+        let y_width = address_width(self.height as usize).max(1);
+        let words: Vec<Vec<bool>> = (0..self.glyph_count as usize)
+            .map(|glyph| {
+                let bounds = self.glyph(glyph).bounds();
+                let mut word = vec![bounds.is_none()];
+                let b = bounds.unwrap_or(Bounds {
+                    x_min: 0,
+                    y_min: 0,
+                    x_max: 0,
+                    y_max: 0,
+                });
+                for (value, width) in [
+                    (b.x_min, x_width),
+                    (b.y_min, y_width),
+                    (b.x_max, x_width),
+                    (b.y_max, y_width),
+                ] {
+                    word.extend((0..width).rev().map(|bit| value & (1 << bit) != 0));
+                }
+                word
+            })
+            .collect();
+        writeln!(
+            out,
+            "// bbox = {{ empty, x_min[{}], y_min[{}], x_max[{}], y_max[{}] }}; blank glyphs set empty",
+            x_width, y_width, x_width, y_width
+        )?;
+        write_rom_module(opts, out, "glyphbbox", "codepoint", "bbox", &words, &[])
+    }
+
+    /// One attribute per glyph, read from `--attr-file` as hex numbers one per line (blank
+    /// lines and # comments skipped), or all zero without one.
+    fn attributes(&self, opts: &Options) -> Result<Vec<u64>, ParseError> {
+        let path = match &opts.attr_file {
+            Some(path) => path,
+            None => return Ok(vec![0; self.glyph_count as usize]),
+        };
+        let width = opts.attr_width.unwrap_or(8);
+        let mut attributes = vec![];
+        for (n, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let digits = line
+                .strip_prefix("0x")
+                .or_else(|| line.strip_prefix("0X"))
+                .unwrap_or(line);
+            match u64::from_str_radix(digits, 16) {
+                Ok(value) if width >= 64 || value >> width == 0 => attributes.push(value),
+                _ => {
+                    return Err(ParseError::InvalidArgument(format!(
+                        "{} line {}: {:?} is not a {}-bit hex attribute",
+                        path,
+                        n + 1,
+                        line,
+                        width
+                    )))
+                }
+            }
+        }
+        if attributes.len() != self.glyph_count as usize {
+            return Err(ParseError::InvalidArgument(format!(
+                "{} has {} attributes but the font has {} glyphs",
+                path,
+                attributes.len(),
+                self.glyph_count
+            )));
+        }
+        Ok(attributes)
+    }
+
+    fn write_attr_rom<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let width = opts.attr_width.unwrap_or(8);
+        let attributes = self.attributes(opts).map_err(|err| match err {
+            ParseError::IoError(err) => err,
+            ParseError::InvalidArgument(message) => {
+                io::Error::new(io::ErrorKind::InvalidData, message)
+            }
+            _ => unreachable!("attributes only fails reading or parsing --attr-file"),
+        })?;
+        let words: Vec<Vec<bool>> = attributes
+            .iter()
+            .map(|value| (0..width).rev().map(|bit| value >> bit & 1 != 0).collect())
+            .collect();
+        write_rom_module(opts, out, "glyphattr", "codepoint", "attr", &words, &[])
+    }
+
+    /// Writes a SystemVerilog package typing each glyph as `glyph_t`, its rows packed top to
+    /// bottom as `row[0]` to `row[height - 1]`, with a `glyph_rows` function looking it up.
+    fn write_struct<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let input_width = address_width(self.glyph_count as usize).max(1);
+        writeln!(out, "package glyph_pkg;")?;
+        if opts.signed_output {
+            writeln!(out, "typedef struct packed signed {{")?;
+        } else {
+            writeln!(out, "typedef struct packed {{")?;
+        }
+        writeln!(out, "    logic [{}:0][{}:0] row;", height - 1, width - 1)?;
+        writeln!(out, "}} glyph_t;")?;
+        writeln!(
+            out,
+            "function automatic glyph_t glyph_rows(input logic [{}:0] codepoint);",
+            input_width - 1
+        )?;
+        writeln!(out, "    case (codepoint)")?;
+        for index in 0..self.glyph_count as usize {
+            let glyph = self.glyph(index);
+            let rows: Vec<String> = (0..height)
+                .rev()
+                .map(|row| format_literal(&glyph.row_bits(row).collect::<Vec<_>>(), opts))
+                .collect();
+            writeln!(
+                out,
+                "        {}: glyph_rows = {{{}}};",
+                address_literal(index, input_width),
+                rows.join(", ")
+            )?;
+        }
+        writeln!(out, "        default: glyph_rows = '0;")?;
+        writeln!(out, "    endcase")?;
+        writeln!(out, "endfunction")?;
+        writeln!(out, "endpackage")
+    }
+
+    /// The code points the lookup matches for each glyph: those of its table entry, with
+    /// `--fold-case` moving every letter whose simple case fold has a glyph to that glyph and
+    /// adding the other case of each letter drawn.
+    fn lookup_codepoints(&self, opts: &Options) -> Vec<Vec<char>> {
+        let table = self.table.as_deref().unwrap_or_default();
+        let mut lists: Vec<Vec<char>> = table
+            .iter()
+            .map(|entry| entry.represented.clone())
+            .collect();
+        let case = match opts.fold_case {
+            Some(case) => case,
+            None => return lists,
+        };
+        let mut glyphs = HashMap::new();
+        for (index, list) in lists.iter().enumerate() {
+            for &c in list {
+                glyphs.entry(c).or_insert(index);
+            }
+        }
+        let candidates: BTreeSet<char> = glyphs
+            .keys()
+            .flat_map(|&c| [Some(c), fold(c, case.opposite())])
+            .flatten()
+            .collect();
+        let moved: Vec<(char, usize)> = candidates
+            .into_iter()
+            .filter_map(|c| {
+                let folded = fold(c, case).filter(|&folded| folded != c)?;
+                Some((c, *glyphs.get(&folded)?))
+            })
+            .collect();
+        for list in lists.iter_mut() {
+            list.retain(|c| !moved.iter().any(|(moved, _)| moved == c));
+        }
+        for (c, index) in moved {
+            lists[index].push(c);
+        }
+        lists
+    }
+
+    fn write_lookup<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let lists = self.lookup_codepoints(opts);
+        let max_codepoint = lists
+            .iter()
+            .flatten()
+            .map(|&c| c as usize)
+            .max()
+            .unwrap_or(0);
+        let unicode_width = address_width(max_codepoint + 1).max(1);
+        let index_width = address_width(self.glyph_count as usize).max(1);
+        writeln!(
+            out,
+            "module glyphlookup ( input wire [{}:0] unicode, output wire [{}:0] codepoint );",
+            unicode_width - 1,
+            index_width - 1
+        )?;
+        write!(out, "assign codepoint = ")?;
+        for (index, list) in lists.iter().enumerate() {
+            if list.is_empty() {
+                continue;
+            }
+            let condition: Vec<String> = list
+                .iter()
+                .map(|&c| {
+                    let word: Vec<bool> = (0..unicode_width)
+                        .rev()
+                        .map(|bit| c as u32 >> bit & 1 != 0)
+                        .collect();
+                    format!("unicode == {}'h{}", unicode_width, hex_digits(&word, opts))
+                })
+                .collect();
+            writeln!(
+                out,
+                "{} ? {}",
+                condition.join(" || "),
+                address_literal(index, index_width)
+            )?;
+            write!(out, "    : ")?;
+        }
+        writeln!(out, "0;")?;
+        writeln!(out, "endmodule")
+    }
+
+    /// Writes a glyphindex ROM of every code point in the table paired with its glyph index,
+    /// sorted by code point, for a binary search in hardware. A code point drawn by several
+    /// glyphs keeps the first.
+    fn write_sorted_index<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let mut glyphs = BTreeMap::new();
+        for (index, entry) in self.table.iter().flatten().enumerate() {
+            for &c in &entry.represented {
+                glyphs.entry(c).or_insert(index);
+            }
+        }
+        let max_codepoint = glyphs.keys().next_back().map_or(0, |&c| c as usize);
+        let unicode_width = address_width(max_codepoint + 1).max(1);
+        let index_width = address_width(self.glyph_count as usize).max(1);
+        let words: Vec<Vec<bool>> = glyphs
+            .iter()
+            .map(|(&c, &index)| {
+                let bits = |value: usize, width: usize| {
+                    (0..width).rev().map(move |bit| value >> bit & 1 != 0)
+                };
+                bits(c as usize, unicode_width)
+                    .chain(bits(index, index_width))
+                    .collect()
+            })
+            .collect();
+        writeln!(
+            out,
+            "// {} entries sorted by code point: pair = {{ unicode[{}:0], codepoint[{}:0] }}",
+            words.len(),
+            unicode_width - 1,
+            index_width - 1
+        )?;
+        write_rom_module(opts, out, "glyphindex", "entry", "pair", &words, &[])
+    }
+
+    /// Writes the glyphs a row at a time for raster scan-out: a glyphrows ROM of every row of
+    /// every glyph, `width` bits with no padding, at address character * height + row, under
+    /// a glyphmap_rows module taking the character and row apart.
+    pub fn write_char_row<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let rows: Vec<Vec<bool>> = (0..self.glyph_count as usize)
+            .flat_map(|index| {
+                let glyph = self.glyph(index);
+                (0..height)
+                    .map(|row| glyph.row_bits(row).collect())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        write_rom_module(opts, out, "glyphrows", "address", "rowdata", &rows, &[])?;
+        writeln!(out)?;
+        let character_width = address_width(self.glyph_count as usize).max(1);
+        let row_width = address_width(height).max(1);
+        let address_width = decoded_width(opts, rows.len());
+        let mut ports: Vec<String> = control_ports(opts)
+            .iter()
+            .map(|port| format!("input wire {}", port))
+            .collect();
+        ports.push(format!("input wire [{}:0] character", character_width - 1));
+        ports.push(format!("input wire [{}:0] row", row_width - 1));
+        ports.push(format!(
+            "output {} [{}:0] rowdata",
+            output_type(opts, "wire"),
+            width - 1
+        ));
+        let mut rom = instance(
+            opts,
+            "glyphrows",
+            "rows",
+            "address",
+            "address",
+            "rowdata",
+            "rowdata",
+        );
+        if opts.with_valid {
+            ports.push("output wire valid".to_string());
+            rom = rom.replace(" );", ", .valid(valid) );");
+        }
+        writeln!(out, "module glyphmap_rows ( {} );", ports.join(", "))?;
+        writeln!(
+            out,
+            "wire [{}:0] address = character * {} + row;",
+            address_width - 1,
+            height
+        )?;
+        writeln!(out, "{}", rom)?;
+        writeln!(out, "endmodule")
+    }
+
+    /// Writes a ROM of glyph columns, for displays shifted out a column at a time: each word
+    /// is a column of the glyph, top pixel first, at address character * width + column.
+    pub fn write_column_scan<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let columns: Vec<Vec<bool>> = (0..self.glyph_count as usize)
+            .flat_map(|index| {
+                let glyph = self.glyph(index);
+                (0..width)
+                    .map(|column| (0..height).map(|row| glyph.pixel(row, column)).collect())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        write_rom_module(
+            opts,
+            out,
+            "glyphcolumns",
+            "address",
+            "coldata",
+            &columns,
+            &[],
+        )?;
+        writeln!(out)?;
+        let character_width = address_width(self.glyph_count as usize).max(1);
+        let column_width = address_width(width).max(1);
+        let address_width = decoded_width(opts, columns.len());
+        let mut ports: Vec<String> = control_ports(opts)
+            .iter()
+            .map(|port| format!("input wire {}", port))
+            .collect();
+        ports.push(format!("input wire [{}:0] character", character_width - 1));
+        ports.push(format!("input wire [{}:0] column", column_width - 1));
+        ports.push(format!(
+            "output {} [{}:0] coldata",
+            output_type(opts, "wire"),
+            height - 1
+        ));
+        let mut rom = instance(
+            opts,
+            "glyphcolumns",
+            "columns",
+            "address",
+            "address",
+            "coldata",
+            "coldata",
+        );
+        if opts.with_valid {
+            ports.push("output wire valid".to_string());
+            rom = rom.replace(" );", ", .valid(valid) );");
+        }
+        writeln!(out, "module glyphmap_columns ( {} );", ports.join(", "))?;
+        writeln!(
+            out,
+            "wire [{}:0] address = character * {} + column;",
+            address_width - 1,
+            width
+        )?;
+        writeln!(out, "{}", rom)?;
+        writeln!(out, "endmodule")
+    }
+
+    pub fn write_verilog<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        backend::for_format(opts.format).emit(self, opts, out)
+    }
+
+    /// With `--emit-header`, writes the font's geometry as `define`s to glyphmap.vh.
+    pub(crate) fn write_header_file(&self, opts: &Options) -> io::Result<()> {
+        if !opts.emit_header {
+            return Ok(());
+        }
+        let mut header = BufWriter::new(File::create(HEADER_FILE)?);
+        self.write_header(&mut header)?;
+        header.flush()
+    }
+
+    fn write_header<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "`define FONT_WIDTH {}", self.width)?;
+        writeln!(out, "`define FONT_HEIGHT {}", self.height)?;
+        writeln!(out, "`define FONT_COUNT {}", self.glyph_count)?;
+        writeln!(out, "`define FONT_RASTER_BITS {}", self.width * self.height)
+    }
+
+    pub(crate) fn write_unmapped_comment<W: Write>(
+        &self,
+        opts: &Options,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let unmapped = self.unmapped_glyphs();
+        if opts.comment && !unmapped.is_empty() {
+            let runs: Vec<String> = unmapped
+                .iter()
+                .map(|&(first, last)| {
+                    if first == last {
+                        first.to_string()
+                    } else {
+                        format!("{}-{}", first, last)
+                    }
+                })
+                .collect();
+            writeln!(out, "// glyphs with no code point: {}", runs.join(", "))?;
+        }
+        Ok(())
+    }
+
+    /// With `--embed-table-comment`, writes the unicode table as a comment block, a line
+    /// of code points per glyph that has any, its sequences after the single code points.
+    pub(crate) fn write_table_comment<W: Write>(
+        &self,
+        opts: &Options,
+        out: &mut W,
+    ) -> io::Result<()> {
+        if !opts.embed_table_comment {
+            return Ok(());
+        }
+        let index_width = self.glyph_count.saturating_sub(1).to_string().len();
+        writeln!(out, "// unicode table: glyph index -> code points")?;
+        for (index, entry) in self.table.iter().flatten().enumerate() {
+            if entry.represented.is_empty() && entry.sequences.is_empty() {
+                continue;
+            }
+            let mut line: Vec<String> = entry
+                .represented
+                .iter()
+                .map(|&c| format!("U+{:04X}", c as u32))
+                .collect();
+            if !entry.sequences.is_empty() {
+                let sequences: Vec<String> = entry
+                    .sequences
+                    .iter()
+                    .map(|&c| format!("U+{:04X}", c as u32))
+                    .collect();
+                line.push(format!("sequences {}", sequences.join(" ")));
+            }
+            writeln!(
+                out,
+                "//   {:>width$}: {}",
+                index,
+                line.join(", "),
+                width = index_width
+            )?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_rom<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        let labels = self.glyph_labels(opts);
+        if let Some(Compression::Rle) = opts.compress {
+            write_rle(out, &words, opts.clock_edge)?;
+        } else if opts.dual_port {
+            write_dual_port(opts, out, &words, &labels)?;
+        } else if let Some(files) = opts.split_files {
+            write_split_files(opts, out, files, &words, &labels)?;
+        } else if opts.split_pages() {
+            write_split(opts, out, "codepoint", "glyph", &words, &labels)?;
+        } else {
+            write_banked(opts, out, "glyphmap", "codepoint", "glyph", &words, &labels)?;
+        }
+        if opts.emit_bbox {
+            writeln!(out)?;
+            self.write_bbox(opts, out)?;
+        }
+        if opts.emit_attr_rom {
+            writeln!(out)?;
+            self.write_attr_rom(opts, out)?;
+        }
+        if opts.emit_struct {
+            writeln!(out)?;
+            self.write_struct(opts, out)?;
+        }
+        if opts.emit_lookup {
+            writeln!(out)?;
+            self.write_lookup(opts, out)?;
+        }
+        if opts.emit_sorted_index {
+            writeln!(out)?;
+            self.write_sorted_index(opts, out)?;
+        }
+        if opts.tb_vcd {
+            writeln!(out)?;
+            write_testbench(opts, out, &words)?;
+        }
+        Ok(())
+    }
+
+    /// Writes this font and `others` as modules glyphmap_font0, glyphmap_font1, ..., each
+    /// padded with blank glyphs to the largest glyph count, under a glyphmap top module
+    /// choosing between them on font_sel.
+    pub fn write_selectable<W: Write>(
+        &self,
+        others: &[PSF],
+        opts: &Options,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let fonts: Vec<&PSF> = std::iter::once(self).chain(others).collect();
+        let depth = fonts
+            .iter()
+            .map(|font| font.glyph_count as usize)
+            .max()
+            .unwrap_or(0);
+        let output_width = self.glyph_words(opts).first().map_or(0, Vec::len);
+        for (n, font) in fonts.iter().enumerate() {
+            let mut words = font.glyph_words(opts);
+            words.resize(depth, blank_word(output_width, opts));
+            let labels = font.glyph_labels(opts);
+            let name = format!("glyphmap_font{}", n);
+            write_banked(opts, out, &name, "codepoint", "glyph", &words, &labels)?;
+            writeln!(out)?;
+        }
+
+        let input_width = decoded_width(opts, depth);
+        let select_width = address_width(fonts.len()).max(1);
+        let mut ports = top_ports(opts, "codepoint", input_width, "glyph", output_width);
+        ports.insert(
+            ports.len() - 2,
+            format!("input wire [{}:0] font_sel", select_width - 1),
+        );
+        writeln!(out, "module glyphmap ( {} );", ports.join(", "))?;
+        let wires: Vec<String> = (0..fonts.len())
+            .map(|n| format!("font{}_glyph", n))
+            .collect();
+        writeln!(out, "wire [{}:0] {};", output_width - 1, wires.join(", "))?;
+        for (n, wire) in wires.iter().enumerate() {
+            writeln!(
+                out,
+                "{}",
+                instance(
+                    opts,
+                    &format!("glyphmap_font{}", n),
+                    &format!("font{}", n),
+                    "codepoint",
+                    "codepoint",
+                    "glyph",
+                    wire
+                )
+            )?;
+        }
+        write!(out, "assign glyph = ")?;
+        for (n, wire) in wires.iter().enumerate().take(wires.len() - 1) {
+            write!(
+                out,
+                "font_sel == {} ? {}\n    : ",
+                address_literal(n, select_width),
+                wire
+            )?;
+        }
+        writeln!(out, "{};", wires[wires.len() - 1])?;
+        writeln!(out, "endmodule")
+    }
+
+    pub(crate) fn write_memh_rom<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let init_file = opts.init_file.as_deref().unwrap_or(MEMH_FILE);
+        let mut hex = BufWriter::new(File::create(init_file)?);
+        self.write_memh_parts(opts, out, &mut hex)?;
+        hex.flush()
+    }
+
+    /// Writes the module to `out` and the words it loads to `hex`.
+    pub(crate) fn write_memh_parts<W: Write, H: Write>(
+        &self,
+        opts: &Options,
+        out: &mut W,
+        hex: &mut H,
+    ) -> io::Result<()> {
+        let init_file = opts.init_file.as_deref().unwrap_or(MEMH_FILE);
+        self.write_memh_words(opts, out, hex, init_file)
+    }
+
+    fn write_memh_words<W: Write, H: Write>(
+        &self,
+        opts: &Options,
+        out: &mut W,
+        hex: &mut H,
+        init_file: &str,
+    ) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        write_memh(opts, hex, &words)?;
+        write_memh_module(opts, out, "codepoint", "glyph", &words, init_file, false)
+    }
+
+    /// Writes the glyphs as a Quartus Memory Initialization File to charmap.mif, or
+    /// `--init-file`, and to `out` a module inferring a ROM initialized from it; with
+    /// `--no-wrapper`, only the MIF, to `out`.
+    pub(crate) fn write_mif_rom<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        if opts.no_wrapper {
+            return write_mif(opts, out, &self.glyph_words(opts));
+        }
+        let init_file = opts.init_file.as_deref().unwrap_or(MIF_FILE);
+        let mut mif = BufWriter::new(File::create(init_file)?);
+        self.write_mif_parts(opts, out, &mut mif)?;
+        mif.flush()
+    }
+
+    /// Writes the module to `out` and the MIF it loads to `mif`.
+    pub(crate) fn write_mif_parts<W: Write, M: Write>(
+        &self,
+        opts: &Options,
+        out: &mut W,
+        mif: &mut M,
+    ) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        write_mif(opts, mif, &words)?;
+        let init_file = opts.init_file.as_deref().unwrap_or(MIF_FILE);
+        write_memh_module(opts, out, "codepoint", "glyph", &words, init_file, true)
+    }
+
+    /// Writes the memh module to `BASE.v` and its words to `BASE.hex` beside it, the module
+    /// loading the hex file by name, and with `--emit-header` glyphmap.vh in the same
+    /// directory. Each goes to a temporary file renamed into place only once all are
+    /// complete, so a failure leaves none of the files this call would have created.
+    pub fn write_rom_files(&self, opts: &Options, base: &str) -> Result<(), ParseError> {
+        let hex_path = format!("{}.hex", base);
+        let hex_name = Path::new(&hex_path)
+            .file_name()
+            .ok_or_else(|| ParseError::InvalidArgument(format!("--emit-rom-file {}", base)))?
+            .to_string_lossy()
+            .into_owned();
+        let mut paths = vec![
+            PathBuf::from(format!("{}.v", base)),
+            PathBuf::from(hex_path),
+        ];
+        if opts.emit_header {
+            paths.push(Path::new(base).with_file_name(HEADER_FILE));
+        }
+        let tmp_paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| {
+                let mut tmp = path.clone().into_os_string();
+                tmp.push(".tmp");
+                PathBuf::from(tmp)
+            })
+            .collect();
+        let mut renamed = vec![];
+        let written = (|| -> io::Result<()> {
+            let mut module = BufWriter::new(File::create(&tmp_paths[0])?);
+            let mut hex = BufWriter::new(File::create(&tmp_paths[1])?);
+            self.write_unmapped_comment(opts, &mut module)?;
+            self.write_table_comment(opts, &mut module)?;
+            self.write_memh_words(opts, &mut module, &mut hex, &hex_name)?;
+            module.flush()?;
+            hex.flush()?;
+            if let Some(tmp) = tmp_paths.get(2) {
+                let mut header = BufWriter::new(File::create(tmp)?);
+                self.write_header(&mut header)?;
+                header.flush()?;
+            }
+            for (tmp, path) in tmp_paths.iter().zip(&paths).rev() {
+                let existed = path.exists();
+                fs::rename(tmp, path)?;
+                if !existed {
+                    renamed.push(path);
+                }
+            }
+            Ok(())
+        })();
+        if written.is_err() {
+            for path in tmp_paths.iter().chain(renamed) {
+                let _ = fs::remove_file(path);
+            }
+        }
+        Ok(written?)
+    }
+
+    pub(crate) fn write_rawhex<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        writeln!(out, "# {} glyphs", words.len())?;
+        for (index, word) in words.iter().enumerate() {
+            writeln!(out, "{}: {}", index, grouped_hex(word, opts))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write_oneline<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        for (index, word) in self.glyph_words(opts).iter().enumerate() {
+            writeln!(out, "{} {}", index, grouped_hex(word, opts))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the glyph words as a Xilinx coefficients file for the Block Memory Generator,
+    /// one word per line in `--radix`.
+    pub(crate) fn write_coe<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        let radix = match opts.radix {
+            Radix::Hex => 16,
+            Radix::Bin => 2,
+            Radix::Dec => 10,
+        };
+        writeln!(
+            out,
+            "; {} glyphs of {} bits",
+            words.len(),
+            words.first().map_or(0, Vec::len)
+        )?;
+        writeln!(out, "memory_initialization_radix={};", radix)?;
+        writeln!(out, "memory_initialization_vector=")?;
+        for (index, word) in words.iter().enumerate() {
+            let end = if index + 1 == words.len() { ';' } else { ',' };
+            writeln!(out, "{}{}", radix_digits(word, opts), end)?;
+        }
+        Ok(())
+    }
+
+    /// The bytes from one glyph of the byte-port ROM to the next: `1 << --addr-shift`, or the
+    /// glyph padded to a multiple of `--align-glyphs`.
+    fn byte_port_stride(&self, opts: &Options) -> usize {
+        match opts.addr_shift {
+            Some(shift) => 1 << shift,
+            None => {
+                let align = opts.align_glyphs.unwrap_or(1);
+                (self.charsize as usize).div_ceil(align) * align
+            }
+        }
+    }
+
+    /// The words of the byte-port ROM: a byte, or with `--nibble-mode` two nibbles, per
+    /// byte of every padded glyph.
+    fn byte_port_depth(&self, opts: &Options) -> usize {
+        let words_per_byte = if opts.nibble_mode { 2 } else { 1 };
+        self.glyph_count as usize * self.byte_port_stride(opts) * words_per_byte
+    }
+
+    pub(crate) fn write_byte_port<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let charsize = self.charsize as usize;
+        let aligned_size = self.byte_port_stride(opts);
+        let mut rom = vec![];
+        for glyph in self.glyphs() {
+            rom.extend_from_slice(glyph);
+            rom.resize(
+                rom.len() + aligned_size - charsize,
+                opts.blank_value.unwrap_or(0),
+            );
+        }
+        if aligned_size != charsize {
+            let padding = rom.len() - self.glyph_count as usize * charsize;
+            note(Note::Aligned {
+                charsize,
+                aligned: aligned_size,
+                padding,
+                glyph_bytes: self.glyph_count as usize * charsize,
+            });
+        }
+        let unit = if opts.nibble_mode { 4 } else { 8 };
+        let words: Vec<Vec<bool>> = rom
+            .chunks_exact(1)
+            .flat_map(|byte| {
+                glyph_word(byte, opts)
+                    .chunks(unit)
+                    .map(|bits| with_parity(bits.to_vec(), opts))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        write_banked(opts, out, "glyphmap", "address", "data", &words, &[])
+    }
+}
+
+fn glyph_word(glyph: &[u8], opts: &Options) -> Vec<bool> {
+    let mut bytes = glyph.to_vec();
+    if opts.lsb_first {
+        for byte in bytes.iter_mut() {
+            *byte = byte.reverse_bits();
+        }
+    }
+    if let Endian::Little = opts.word_endian {
+        bytes.reverse();
+    }
+    bits(&bytes)
+}
+
+#[cfg(feature = "unicode-names")]
+fn codepoint_label(c: char) -> String {
+    match unicode_names2::name(c) {
+        Some(name) => format!("U+{:04X} {}", c as u32, name),
+        None => format!("U+{:04X}", c as u32),
+    }
+}
+
+#[cfg(not(feature = "unicode-names"))]
+fn codepoint_label(c: char) -> String {
+    format!("U+{:04X}", c as u32)
+}
+
+/// The simple (one to one) mapping of `c` to `case`, or None when it has none.
+fn fold(c: char, case: FoldCase) -> Option<char> {
+    let mut folded: Vec<char> = match case {
+        FoldCase::Upper => c.to_uppercase().collect(),
+        FoldCase::Lower => c.to_lowercase().collect(),
+    };
+    match folded.len() {
+        1 => folded.pop(),
+        _ => None,
+    }
+}
+
+fn with_parity(mut word: Vec<bool>, opts: &Options) -> Vec<bool> {
+    if let Some(parity) = &opts.parity {
+        let odd_ones = word.iter().filter(|&&bit| bit).count() % 2 == 1;
+        word.push(match parity {
+            Parity::Even => odd_ones,
+            Parity::Odd => !odd_ones,
+        });
+    }
+    word
+}
+
+fn bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |bit| byte & (1 << bit) != 0))
+        .collect()
+}
+
+pub(crate) fn address_width(depth: usize) -> usize {
+    (depth as f64).log2().ceil() as usize
+}
+
+/// The address width of a ROM of `depth` words, wide enough to reach past `--base-address`.
+fn decoded_width(opts: &Options, depth: usize) -> usize {
+    address_width(depth + opts.base_address.unwrap_or(0)).max(1)
+}
+
+/// Writes a glyphmap_tb module presenting every codepoint in turn for `TB_STEP` ns each,
+/// clocking glyphmap with a `TB_STEP` ns clock when it is registered, and dumping its
+/// signals to `TB_VCD` before finishing once the last glyph is through the registers.
+fn write_testbench<W: Write>(opts: &Options, out: &mut W, words: &[Vec<bool>]) -> io::Result<()> {
+    const TB_STEP: usize = 10;
+    const TB_VCD: &str = "glyphmap.vcd";
+    let input_width = decoded_width(opts, words.len());
+    let output_width = words.first().map_or(0, Vec::len);
+    let latency = match opts.pipeline {
+        Some(2) => 2,
+        _ if opts.registered() => 1,
+        _ => 0,
+    };
+    writeln!(out, "`timescale 1ns / 1ps")?;
+    writeln!(out, "module glyphmap_tb;")?;
+    let mut connections = vec![];
+    for port in control_ports(opts) {
+        let initial = match port {
+            "clk" => "0",
+            "rst" if opts.reset_active_low => "1",
+            "rst" => "0",
+            _ => "1",
+        };
+        writeln!(out, "reg {} = {};", port, initial)?;
+        connections.push(format!(".{}({})", port, port));
+    }
+    writeln!(out, "reg [{}:0] codepoint = 0;", input_width - 1)?;
+    writeln!(out, "wire [{}:0] glyph;", output_width - 1)?;
+    connections.push(".codepoint(codepoint)".to_string());
+    connections.push(".glyph(glyph)".to_string());
+    if opts.with_valid {
+        writeln!(out, "wire valid;")?;
+        connections.push(".valid(valid)".to_string());
+    }
+    writeln!(out, "glyphmap dut ( {} );", connections.join(", "))?;
+    if opts.registered() {
+        writeln!(out, "always #{} clk = ~clk;", TB_STEP / 2)?;
+    }
+    writeln!(out, "initial begin")?;
+    writeln!(out, "    $dumpfile(\"{}\");", TB_VCD)?;
+    writeln!(out, "    $dumpvars(0, glyphmap_tb);")?;
+    // Step between clock edges so neither edge races the new codepoint
+    writeln!(out, "    #2;")?;
+    writeln!(out, "    repeat ({}) begin", words.len() - 1)?;
+    writeln!(out, "        #{} codepoint = codepoint + 1;", TB_STEP)?;
+    writeln!(out, "    end")?;
+    writeln!(out, "    #{} $finish;", TB_STEP * (latency + 1))?;
+    writeln!(out, "end")?;
+    writeln!(out, "endmodule")
+}
+
+fn control_ports(opts: &Options) -> Vec<&'static str> {
+    let mut ports = vec![];
+    if opts.registered() {
+        ports.push("clk");
+    }
+    if opts.with_reset {
+        ports.push("rst");
+    }
+    if opts.with_enable {
+        ports.push("en");
+    }
+    if opts.with_valid {
+        ports.push("ren");
+    }
+    ports
+}
+
+fn write_split<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    address: &str,
+    data: &str,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let (low, high) = words.split_at(words.len() / 2);
+    let (low_labels, high_labels) = labels.split_at(labels.len().min(low.len()));
+    write_banked(opts, out, "glyphmap_low", address, data, low, low_labels)?;
+    writeln!(out)?;
+    write_banked(opts, out, "glyphmap_high", address, data, high, high_labels)?;
+    writeln!(out)?;
+
+    let input_width = address_width(low.len());
+    let output_width = words.first().map_or(0, Vec::len);
+    let mut ports = top_ports(opts, address, input_width, data, output_width);
+    ports.insert(ports.len() - 2, "input wire page".to_string());
+    writeln!(out, "module glyphmap ( {} );", ports.join(", "))?;
+    writeln!(
+        out,
+        "wire [{}:0] low_{}, high_{};",
+        output_width - 1,
+        data,
+        data
+    )?;
+    for half in ["low", "high"] {
+        writeln!(
+            out,
+            "{}",
+            instance(
+                opts,
+                &format!("glyphmap_{}", half),
+                half,
+                address,
+                address,
+                data,
+                &format!("{}_{}", half, data)
+            )
+        )?;
+    }
+    writeln!(
+        out,
+        "assign {} = page ? high_{} : low_{};",
+        data, data, data
+    )?;
+    writeln!(out, "endmodule")
+}
+
+/// The path of a file written alongside the module: next to `--output`, or in the current
+/// directory when the module goes to stdout.
+fn beside_output(opts: &Options, name: &str) -> PathBuf {
+    opts.output
+        .as_deref()
+        .and_then(|output| Path::new(output).parent())
+        .unwrap_or_else(|| Path::new(""))
+        .join(name)
+}
+
+fn write_split_files<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    files: usize,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let part_size = words.len().div_ceil(files).next_power_of_two().max(2);
+    let part_width = address_width(part_size);
+    let input_width = address_width(words.len()).max(part_width);
+    let output_width = words.first().map_or(0, Vec::len);
+    let parts: Vec<Vec<Vec<bool>>> = words
+        .chunks(part_size)
+        .map(|chunk| {
+            let mut part_words = chunk.to_vec();
+            part_words.resize(part_size, blank_word(output_width, opts));
+            part_words
+        })
+        .collect();
+    for (part, part_words) in parts.iter().enumerate() {
+        let start = (part * part_size).min(labels.len());
+        let end = (start + part_size).min(labels.len());
+        let path = beside_output(opts, &format!("charmap_{}.v", part));
+        let mut file = BufWriter::new(File::create(path)?);
+        write_banked(
+            opts,
+            &mut file,
+            &format!("glyphmap_part{}", part),
+            "codepoint",
+            "glyph",
+            part_words,
+            &labels[start..end],
+        )?;
+        file.flush()?;
+    }
+
+    let ports = top_ports(opts, "codepoint", input_width, "glyph", output_width);
+    writeln!(out, "module glyphmap ( {} );", ports.join(", "))?;
+    for part in 0..parts.len() {
+        writeln!(out, "wire [{}:0] part{}_glyph;", output_width - 1, part)?;
+        writeln!(
+            out,
+            "{}",
+            instance(
+                opts,
+                &format!("glyphmap_part{}", part),
+                &format!("part{}", part),
+                "codepoint",
+                &format!("codepoint[{}:0]", part_width - 1),
+                "glyph",
+                &format!("part{}_glyph", part)
+            )
+        )?;
+    }
+    if parts.len() == 1 {
+        writeln!(out, "assign glyph = part0_glyph;")?;
+    } else {
+        let select_width = input_width - part_width;
+        let select = format!("codepoint[{}:{}]", input_width - 1, part_width);
+        write!(out, "assign glyph = ")?;
+        for part in 0..parts.len() {
+            if part != 0 {
+                write!(out, "    : ")?;
+            }
+            writeln!(
+                out,
+                "{} == {} ? part{}_glyph",
+                select,
+                address_literal(
+                    if opts.invert_address {
+                        invert(part, select_width)
+                    } else {
+                        part
+                    },
+                    select_width
+                ),
+                part
+            )?;
+        }
+        writeln!(out, "    : {};", blank_literal(output_width, opts))?;
+    }
+    writeln!(out, "endmodule")
+}
+
+fn write_banked<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    name: &str,
+    address: &str,
+    data: &str,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let output_width = words.first().map_or(0, Vec::len);
+    let bank_width = match opts.bank_width {
+        Some(bank_width) if bank_width < output_width => bank_width,
+        _ => return write_rom_module(opts, out, name, address, data, words, labels),
+    };
+    let banks = output_width.div_ceil(bank_width);
+    for bank in 0..banks {
+        let lsb = bank * bank_width;
+        let msb = (lsb + bank_width).min(output_width);
+        let bank_words: Vec<Vec<bool>> = words
+            .iter()
+            .map(|word| word[output_width - msb..output_width - lsb].to_vec())
+            .collect();
+        let bank_name = format!("{}_bank{}", name, bank);
+        write_rom_module(opts, out, &bank_name, address, data, &bank_words, labels)?;
+        writeln!(out)?;
+    }
+
+    let input_width = decoded_width(opts, words.len());
+    let ports = top_ports(opts, address, input_width, data, output_width);
+    writeln!(out, "module {} ( {} );", name, ports.join(", "))?;
+    for bank in 0..banks {
+        let lsb = bank * bank_width;
+        let msb = (lsb + bank_width).min(output_width);
+        writeln!(
+            out,
+            "{}",
+            instance(
+                opts,
+                &format!("{}_bank{}", name, bank),
+                &format!("bank{}", bank),
+                address,
+                address,
+                data,
+                &format!("{}[{}:{}]", data, msb - 1, lsb)
+            )
+        )?;
+    }
+    writeln!(out, "endmodule")
+}
+
+fn top_ports(
+    opts: &Options,
+    address: &str,
+    input_width: usize,
+    data: &str,
+    output_width: usize,
+) -> Vec<String> {
+    let mut ports: Vec<String> = control_ports(opts)
+        .iter()
+        .map(|port| format!("input wire {}", port))
+        .collect();
+    ports.push(format!("input wire [{}:0] {}", input_width - 1, address));
+    ports.push(format!(
+        "output {} [{}:0] {}",
+        output_type(opts, "wire"),
+        output_width - 1,
+        data
+    ));
+    ports
+}
+
+fn instance(
+    opts: &Options,
+    module: &str,
+    name: &str,
+    address: &str,
+    address_signal: &str,
+    data: &str,
+    data_signal: &str,
+) -> String {
+    let mut connections: Vec<String> = control_ports(opts)
+        .iter()
+        .map(|port| format!(".{}({})", port, port))
+        .collect();
+    connections.push(format!(".{}({})", address, address_signal));
+    connections.push(format!(".{}({})", data, data_signal));
+    format!("{} {} ( {} );", module, name, connections.join(", "))
+}
+
+pub(crate) fn write_rom_module<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    name: &str,
+    address: &str,
+    data: &str,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let input_width = decoded_width(opts, words.len());
+    let base = opts.base_address.unwrap_or(0);
+    let output_width = words.first().map_or(0, Vec::len);
+    let mut ports = module_ports(opts, address, input_width - 1, data, output_width - 1);
+    if let Some(style) = opts.rom_style.filter(|_| !opts.emit_initial_array) {
+        let output = ports.pop().unwrap_or_default();
+        ports.push(format!("(* rom_style = \"{}\" *) {}", style, output));
+    }
+    if opts.with_valid {
+        ports.push("output reg valid".to_string());
+    }
+    writeln!(out, "module {} ( {} );", name, ports.join(", "))?;
+    let address = &write_address_register(opts, out, address, input_width - 1)?;
+    write_valid_register(opts, out)?;
+    if opts.emit_muxtree {
+        write_mux_tree(opts, out, address, input_width, data, words)?;
+        return writeln!(out, "endmodule");
+    }
+    if opts.emit_initial_array {
+        write_initial_array(opts, out, words, labels)?;
+        write_assignment_head(opts, out, data, output_width)?;
+        writeln!(out, "mem[{}];", mem_index(opts, address, input_width))?;
+        return writeln!(out, "endmodule");
+    }
+    for (word, label) in words.iter().zip(labels) {
+        if let Some(name) = &label.name {
+            writeln!(
+                out,
+                "localparam [{}:0] {} = {};",
+                output_width - 1,
+                name,
+                format_literal(word, opts)
+            )?;
+        }
+    }
+    let value = |glyph: usize, word: &[bool]| match labels.get(glyph) {
+        Some(Label { dontcare: true, .. }) => format!("{}'bx", word.len()),
+        Some(Label {
+            name: Some(name), ..
+        }) => name.clone(),
+        _ => format_literal(word, opts),
+    };
+    let indent = write_assignment_head(opts, out, data, output_width)?;
+    let mut arms: Vec<(usize, usize, &Vec<bool>)> = vec![];
+    for (i, word) in words.iter().enumerate() {
+        match arms.last_mut() {
+            Some((_, last, previous)) if opts.collapse && *previous == word => *last = i,
+            _ => arms.push((i, i, word)),
+        }
+    }
+    if opts.collapse {
+        note(Note::Collapsed {
+            module: name.to_string(),
+            words: words.len(),
+            arms: arms.len(),
+        });
+    }
+    let full = words.len() == 1 << input_width && arms.len() > 1;
+    if opts.no_default_arm && !full {
+        note(Note::DefaultArmKept {
+            module: name.to_string(),
+            words: words.len(),
+            addresses: 1 << input_width,
+        });
+    }
+    let omit_default = opts.no_default_arm && full;
+    for (n, &(first, last, word)) in arms.iter().enumerate() {
+        if n != 0 {
+            write!(out, "{}: ", indent)?;
+        }
+        let comment = match (labels.get(first), labels.get(last)) {
+            (Some(label), _) if first == last && !label.comment.is_empty() => {
+                format!(" // {}", label.comment)
+            }
+            (Some(first_label), Some(last_label))
+                if first != last && !first_label.comment.is_empty() =>
+            {
+                format!(" // {} .. {}", first_label.comment, last_label.comment)
+            }
+            _ => String::new(),
+        };
+        if omit_default && n + 1 == arms.len() {
+            writeln!(out, "{};{}", value(first, word), comment)?;
+            break;
+        }
+        let (low, high) = if opts.invert_address {
+            (invert(last, input_width), invert(first, input_width))
+        } else {
+            (first, last)
+        };
+        let (low, high) = if opts.reverse_address_bits {
+            (
+                reverse_bits(low, input_width),
+                reverse_bits(high, input_width),
+            )
+        } else {
+            (low + base, high + base)
+        };
+        let condition = if first == last {
+            format!("{} == {}", address, address_literal(low, input_width))
+        } else {
+            format!(
+                "{} >= {} && {} <= {}",
+                address,
+                address_literal(low, input_width),
+                address,
+                address_literal(high, input_width)
+            )
+        };
+        writeln!(out, "{} ? {}{}", condition, value(first, word), comment)?;
+    }
+    if !omit_default {
+        writeln!(out, "{}: {};", indent, blank_literal(output_width, opts))?;
+    }
+    writeln!(out, "endmodule")
+}
+
+/// Writes the glyphs into a mem array read on clk through two independent ports, the shape
+/// tools infer a dual-port block ROM from.
+fn write_dual_port<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let input_width = decoded_width(opts, words.len());
+    let output_width = words.first().map_or(0, Vec::len);
+    let mut ports = vec!["input wire clk".to_string()];
+    for port in &["a", "b"] {
+        ports.push(format!(
+            "input wire [{}:0] codepoint_{}",
+            input_width - 1,
+            port
+        ));
+        ports.push(format!(
+            "output {} [{}:0] glyph_{}",
+            output_type(opts, "reg"),
+            output_width - 1,
+            port
+        ));
+    }
+    writeln!(out, "module glyphmap ( {} );", ports.join(", "))?;
+    write_initial_array(opts, out, words, labels)?;
+    writeln!(out, "always @({} clk) begin", opts.clock_edge.keyword())?;
+    for port in &["a", "b"] {
+        writeln!(
+            out,
+            "    glyph_{} {} mem[{}];",
+            port,
+            opts.assign_style.operator(),
+            mem_index(opts, &format!("codepoint_{}", port), input_width)
+        )?;
+    }
+    writeln!(out, "end")?;
+    writeln!(out, "endmodule")
+}
+
+/// Declares the mem array holding `words` and fills it in an initial block.
+fn write_initial_array<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    words: &[Vec<bool>],
+    labels: &[Label],
+) -> io::Result<()> {
+    let output_width = words.first().map_or(0, Vec::len);
+    if let Some(attribute) = memory_attribute(opts) {
+        writeln!(out, "{}", attribute)?;
+    }
+    writeln!(
+        out,
+        "reg [{}:0] mem [0:{}];",
+        output_width - 1,
+        words.len() - 1
+    )?;
+    writeln!(out, "initial begin")?;
+    for (i, word) in words.iter().enumerate() {
+        write!(out, "    mem[{}] = {};", i, format_literal(word, opts))?;
+        match labels.get(i) {
+            Some(label) if !label.comment.is_empty() => writeln!(out, " // {}", label.comment)?,
+            _ => writeln!(out)?,
+        }
+    }
+    writeln!(out, "end")
+}
+
+fn write_mux_tree<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    address: &str,
+    input_width: usize,
+    data: &str,
+    words: &[Vec<bool>],
+) -> io::Result<()> {
+    let output_width = words.first().map_or(0, Vec::len);
+    let blank = blank_word(output_width, opts);
+    let mut level: Vec<String> = (0..1 << input_width)
+        .map(|i| format_literal(words.get(i).unwrap_or(&blank), opts))
+        .collect();
+    for bit in 0..input_width {
+        level = level
+            .chunks_exact(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let wire = format!("mux{}_{}", bit + 1, i);
+                writeln!(
+                    out,
+                    "wire [{}:0] {} = {}[{}] ? {} : {};",
+                    output_width - 1,
+                    wire,
+                    address,
+                    if opts.reverse_address_bits {
+                        input_width - 1 - bit
+                    } else {
+                        bit
+                    },
+                    pair[!opts.invert_address as usize],
+                    pair[opts.invert_address as usize]
+                )?;
+                Ok(wire)
+            })
+            .collect::<io::Result<_>>()?;
+    }
+    write_assignment_head(opts, out, data, output_width)?;
+    writeln!(out, "{};", level[0])
+}
+
+fn write_memh_module<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    address: &str,
+    data: &str,
+    words: &[Vec<bool>],
+    init_file: &str,
+    mif: bool,
+) -> io::Result<()> {
+    let input_width = decoded_width(opts, words.len());
+    let output_width = words.first().map_or(0, Vec::len);
+    let mut ports = module_ports(opts, address, "ADDR_WIDTH-1", data, "DATA_WIDTH-1");
+    if opts.with_valid {
+        ports.push("output reg valid".to_string());
+    }
+    writeln!(
+        out,
+        "module glyphmap #( parameter INIT_FILE = \"{}\", parameter ADDR_WIDTH = {}, \
+         parameter DATA_WIDTH = {}, parameter DEPTH = {} ) ( {} );",
+        init_file,
+        input_width,
+        output_width,
+        words.len(),
+        ports.join(", ")
+    )?;
+    if let Some(attribute) = memory_attribute(opts) {
+        writeln!(out, "{}", attribute)?;
+    }
+    if mif {
+        // Quartus takes the file from the attribute and ignores initial blocks
+        writeln!(out, "(* ram_init_file = \"{}\" *)", init_file)?;
+        writeln!(out, "reg [DATA_WIDTH-1:0] mem [0:DEPTH-1];")?;
+    } else {
+        writeln!(out, "reg [DATA_WIDTH-1:0] mem [0:DEPTH-1];")?;
+        writeln!(out, "initial $readmemh(INIT_FILE, mem);")?;
+    }
+    let address = &write_address_register(opts, out, address, "ADDR_WIDTH-1")?;
+    write_valid_register(opts, out)?;
+    write_assignment_head(opts, out, data, output_width)?;
+    writeln!(out, "mem[{}];", mem_index(opts, address, input_width))?;
+    writeln!(out, "endmodule")
+}
+
+fn memory_attribute(opts: &Options) -> Option<String> {
+    match (opts.rom_style, opts.target) {
+        (Some(style), _) => Some(format!("(* rom_style = \"{}\" *)", style)),
+        (None, Target::Generic) => None,
+        (None, Target::Ice40) => Some("(* rom_style = \"block\" *)".to_string()),
+        (None, Target::Ecp5) => Some("(* syn_romstyle = \"EBR\" *)".to_string()),
+    }
+}
+
+fn write_memh<W: Write>(opts: &Options, out: &mut W, words: &[Vec<bool>]) -> io::Result<()> {
+    for word in words {
+        writeln!(out, "{}", hex_digits(word, opts))?;
+    }
+    Ok(())
+}
+
+/// Writes a Memory Initialization File: the WIDTH, DEPTH and radix header, then an
+/// "address : word;" line per glyph, the word in `--radix`.
+fn write_mif<W: Write>(opts: &Options, out: &mut W, words: &[Vec<bool>]) -> io::Result<()> {
+    writeln!(out, "WIDTH={};", words.first().map_or(0, Vec::len))?;
+    writeln!(out, "DEPTH={};", words.len())?;
+    writeln!(out, "ADDRESS_RADIX=UNS;")?;
+    let radix = match opts.radix {
+        Radix::Hex => "HEX",
+        Radix::Bin => "BIN",
+        Radix::Dec => "UNS",
+    };
+    writeln!(out, "DATA_RADIX={};", radix)?;
+    writeln!(out)?;
+    writeln!(out, "CONTENT BEGIN")?;
+    for (address, word) in words.iter().enumerate() {
+        writeln!(out, "    {} : {};", address, radix_digits(word, opts))?;
+    }
+    writeln!(out, "END;")
+}
+
+fn module_ports<M: Display>(
+    opts: &Options,
+    address: &str,
+    input_msb: M,
+    data: &str,
+    output_msb: M,
+) -> Vec<String> {
+    let mut ports: Vec<String> = control_ports(opts)
+        .iter()
+        .map(|port| format!("input wire {}", port))
+        .collect();
+    ports.push(format!("input wire [{}:0] {}", input_msb, address));
+    ports.push(format!(
+        "output {} [{}:0] {}",
+        output_type(opts, if opts.registered() { "reg" } else { "wire" }),
+        output_msb,
+        data
+    ));
+    ports
+}
+
+/// The `kind` of a ROM data output, declared signed with `--signed-output`.
+fn output_type(opts: &Options, kind: &str) -> String {
+    if opts.signed_output {
+        format!("{} signed", kind)
+    } else {
+        kind.to_string()
+    }
+}
+
+/// With `--pipeline 2`, registers `address` into `address_r` on clk (while en is high, with
+/// `--with-enable`) and returns the signal the ROM should decode.
+fn write_address_register<W: Write, M: Display>(
+    opts: &Options,
+    out: &mut W,
+    address: &str,
+    input_msb: M,
+) -> io::Result<String> {
+    if opts.pipeline != Some(2) {
+        return Ok(address.to_string());
+    }
+    let assign = opts.assign_style.operator();
+    writeln!(out, "reg [{}:0] {}_r;", input_msb, address)?;
+    writeln!(out, "always @({} clk)", opts.clock_edge.keyword())?;
+    if opts.with_enable {
+        writeln!(out, "    if (en)")?;
+        writeln!(out, "        {}_r {} {};", address, assign, address)?;
+    } else {
+        writeln!(out, "    {}_r {} {};", address, assign, address)?;
+    }
+    Ok(format!("{}_r", address))
+}
+
+/// What the output register loads on: en with `--with-enable`, ren with `--with-valid`.
+fn load_condition(opts: &Options) -> Option<&'static str> {
+    match (opts.with_enable, opts.with_valid) {
+        (true, true) => Some("en && ren"),
+        (true, false) => Some("en"),
+        (false, true) => Some("ren"),
+        (false, false) => None,
+    }
+}
+
+/// Opens an always block on the `--clock-edge` of clk, also sensitive to rst with `--reset-async`.
+fn write_always_head<W: Write>(opts: &Options, out: &mut W) -> io::Result<()> {
+    let rst_edge = if opts.reset_active_low {
+        "negedge"
+    } else {
+        "posedge"
+    };
+    let clk_edge = opts.clock_edge.keyword();
+    if opts.with_reset && opts.reset_async {
+        writeln!(out, "always @({} clk or {} rst)", clk_edge, rst_edge)
+    } else {
+        writeln!(out, "always @({} clk)", clk_edge)
+    }
+}
+
+fn reset_condition(opts: &Options) -> &'static str {
+    if opts.reset_active_low {
+        "!rst"
+    } else {
+        "rst"
+    }
+}
+
+/// With `--with-valid`, registers valid so it rises the clock after ren, alongside the glyph
+/// it loaded.
+fn write_valid_register<W: Write>(opts: &Options, out: &mut W) -> io::Result<()> {
+    if !opts.with_valid {
+        return Ok(());
+    }
+    write_always_head(opts, out)?;
+    let load = load_condition(opts).unwrap_or("ren");
+    let assign = opts.assign_style.operator();
+    if opts.with_reset {
+        writeln!(out, "    if ({})", reset_condition(opts))?;
+        writeln!(out, "        valid {} 0;", assign)?;
+        writeln!(out, "    else")?;
+        writeln!(out, "        valid {} {};", assign, load)
+    } else {
+        writeln!(out, "    valid {} {};", assign, load)
+    }
+}
+
+fn write_assignment_head<W: Write>(
+    opts: &Options,
+    out: &mut W,
+    data: &str,
+    width: usize,
+) -> io::Result<String> {
+    if opts.registered() {
+        let assign = opts.assign_style.operator();
+        write_always_head(opts, out)?;
+        let mut indent = "    ".to_string();
+        if opts.with_reset {
+            writeln!(out, "{}if ({})", indent, reset_condition(opts))?;
+            writeln!(
+                out,
+                "{}    {} {} {};",
+                indent,
+                data,
+                assign,
+                blank_literal(width, opts)
+            )?;
+            if let Some(condition) = load_condition(opts) {
+                writeln!(out, "{}else if ({})", indent, condition)?;
+            } else {
+                writeln!(out, "{}else", indent)?;
+            }
+            indent.push_str("    ");
+        } else if let Some(condition) = load_condition(opts) {
+            writeln!(out, "{}if ({})", indent, condition)?;
+            indent.push_str("    ");
+        }
+        write!(out, "{}{} {} ", indent, data, assign)?;
+        Ok(indent + "    ")
+    } else {
+        write!(out, "assign {} = ", data)?;
+        Ok("    ".to_string())
+    }
+}
+
+/// A word of the `--blank-value` byte repeated from the least significant bit up, or zero.
+pub(crate) fn blank_word(width: usize, opts: &Options) -> Vec<bool> {
+    let byte = opts.blank_value.unwrap_or(0);
+    (0..width)
+        .rev()
+        .map(|bit| byte & (1 << (bit % 8)) != 0)
+        .collect()
+}
+
+pub(crate) fn blank_literal(width: usize, opts: &Options) -> String {
+    match opts.blank_value {
+        Some(_) => format_literal(&blank_word(width, opts), opts),
+        None => "0".to_string(),
+    }
+}
+
+fn invert(address: usize, input_width: usize) -> usize {
+    !address & ((1 << input_width) - 1)
+}
+
+fn reverse_bits(address: usize, input_width: usize) -> usize {
+    (0..input_width)
+        .filter(|bit| address & (1 << bit) != 0)
+        .fold(0, |reversed, bit| reversed | 1 << (input_width - 1 - bit))
+}
+
+/// How a mem array indexed by glyph is read at `address`, undoing `--invert-address` and
+/// `--reverse-address-bits`.
+fn mem_index(opts: &Options, address: &str, input_width: usize) -> String {
+    let index = if opts.reverse_address_bits {
+        let bits: Vec<String> = (0..input_width)
+            .map(|bit| format!("{}[{}]", address, bit))
+            .collect();
+        format!("{{{}}}", bits.join(", "))
+    } else {
+        address.to_string()
+    };
+    if opts.invert_address {
+        format!("~{}", index)
+    } else {
+        index
+    }
+}
+
+fn address_literal(address: usize, input_width: usize) -> String {
+    format!(
+        "{}'b{:0>input_width$b}",
+        input_width,
+        address,
+        input_width = input_width
+    )
+}
+
+fn format_literal(word: &[bool], opts: &Options) -> String {
+    let (letter, group) = match opts.radix {
+        Radix::Hex => ('h', 2),
+        Radix::Bin => ('b', 8),
+        Radix::Dec => ('d', 0),
+    };
+    let digits = radix_digits(word, opts);
+    format!(
+        "{}'{}{}",
+        word.len(),
+        letter,
+        grouped(&digits, group * opts.group_bytes.unwrap_or(0), '_')
+    )
+}
+
+/// Separates `digits` with `sep` into groups of `size` counted from the right, the way
+/// Verilog allows `_` between the digits of a literal; a size of 0 leaves them whole.
+fn grouped(digits: &str, size: usize, sep: char) -> String {
+    if size == 0 {
+        return digits.to_string();
+    }
+    let mut s = String::new();
+    for (n, c) in digits.chars().enumerate() {
+        if n != 0 && (digits.len() - n).is_multiple_of(size) {
+            s.push(sep);
+        }
+        s.push(c);
+    }
+    s
+}
+
+/// The digits of `word` in `--radix`, without the width and radix of a Verilog literal.
+fn radix_digits(word: &[bool], opts: &Options) -> String {
+    let mut s = String::new();
+    match opts.radix {
+        Radix::Hex => s.push_str(&hex_digits(word, opts)),
+        Radix::Bin => s.extend(word.iter().map(|&bit| if bit { '1' } else { '0' })),
+        Radix::Dec => {
+            let mut n: Vec<u8> = padded(word, 8)
+                .chunks(8)
+                .map(|byte| byte.iter().fold(0, |acc, &bit| acc << 1 | bit as u8))
+                .collect();
+            let mut digits = vec![];
+            while n.iter().any(|&byte| byte != 0) {
+                let mut remainder = 0u32;
+                for byte in n.iter_mut() {
+                    let current = remainder * 256 + *byte as u32;
+                    *byte = (current / 10) as u8;
+                    remainder = current % 10;
+                }
+                digits.push(char::from(b'0' + remainder as u8));
+            }
+            if digits.is_empty() {
+                digits.push('0');
+            }
+            s.extend(digits.iter().rev());
+        }
+    }
+    s
+}
+
+/// The hex digits of `word` in groups of `--group-bytes` bytes split by `--group-sep`.
+fn grouped_hex(word: &[bool], opts: &Options) -> String {
+    grouped(
+        &hex_digits(word, opts),
+        2 * opts.group_bytes.unwrap_or(0),
+        opts.group_sep.unwrap_or('_'),
+    )
+}
+
+pub(crate) fn hex_digits(word: &[bool], opts: &Options) -> String {
+    padded(word, 4)
+        .chunks(4)
+        .map(|nibble| {
+            let value = nibble.iter().fold(0, |acc, &bit| acc << 1 | bit as u32);
+            if opts.hex_lowercase {
+                format!("{:x}", value)
+            } else {
+                format!("{:X}", value)
+            }
+        })
+        .collect()
+}
+
+fn padded(word: &[bool], multiple: usize) -> Vec<bool> {
+    let mut padded = vec![false; word.len().div_ceil(multiple) * multiple - word.len()];
+    padded.extend_from_slice(word);
+    padded
+}
@@ -0,0 +1,238 @@
+use super::{Options, ParseError, PSF};
+use crate::verilog::hex_digits;
+use std::io::{self, Write};
+
+const RESERVED: &[&str] = &[
+    "abs",
+    "access",
+    "after",
+    "alias",
+    "all",
+    "and",
+    "architecture",
+    "array",
+    "assert",
+    "assume",
+    "attribute",
+    "begin",
+    "block",
+    "body",
+    "buffer",
+    "bus",
+    "case",
+    "component",
+    "configuration",
+    "constant",
+    "context",
+    "cover",
+    "default",
+    "disconnect",
+    "downto",
+    "else",
+    "elsif",
+    "end",
+    "entity",
+    "exit",
+    "fairness",
+    "file",
+    "for",
+    "force",
+    "function",
+    "generate",
+    "generic",
+    "group",
+    "guarded",
+    "if",
+    "impure",
+    "in",
+    "inertial",
+    "inout",
+    "is",
+    "label",
+    "library",
+    "linkage",
+    "literal",
+    "loop",
+    "map",
+    "mod",
+    "nand",
+    "new",
+    "next",
+    "nor",
+    "not",
+    "null",
+    "of",
+    "on",
+    "open",
+    "or",
+    "others",
+    "out",
+    "package",
+    "parameter",
+    "port",
+    "postponed",
+    "procedure",
+    "process",
+    "property",
+    "protected",
+    "pure",
+    "range",
+    "record",
+    "register",
+    "reject",
+    "release",
+    "rem",
+    "report",
+    "restrict",
+    "return",
+    "rol",
+    "ror",
+    "select",
+    "sequence",
+    "severity",
+    "shared",
+    "signal",
+    "sla",
+    "sll",
+    "sra",
+    "srl",
+    "strong",
+    "subtype",
+    "then",
+    "to",
+    "transport",
+    "type",
+    "unaffected",
+    "units",
+    "until",
+    "use",
+    "variable",
+    "vmode",
+    "vprop",
+    "vunit",
+    "wait",
+    "when",
+    "while",
+    "with",
+    "xnor",
+    "xor",
+];
+
+pub(crate) fn check_identifier(name: &str) -> Result<(), ParseError> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.ends_with('_')
+        && !name.contains("__")
+        && !RESERVED.contains(&name.to_ascii_lowercase().as_str());
+    if valid {
+        Ok(())
+    } else {
+        Err(ParseError::InvalidArgument(format!(
+            "{} is not a valid VHDL identifier",
+            name
+        )))
+    }
+}
+
+impl PSF {
+    pub fn write_vhdl<W: Write>(&self, opts: &Options, out: &mut W) -> io::Result<()> {
+        let words = self.glyph_words(opts);
+        let name = opts.package.as_deref().unwrap_or("font_pkg");
+        let output_width = words.first().map_or(0, Vec::len);
+        let vector = if opts.signed_output {
+            "signed"
+        } else {
+            "std_logic_vector"
+        };
+        writeln!(out, "library ieee;")?;
+        writeln!(out, "use ieee.std_logic_1164.all;")?;
+        if opts.signed_output {
+            writeln!(out, "use ieee.numeric_std.all;")?;
+        }
+        writeln!(out)?;
+        writeln!(out, "package {} is", name)?;
+        if opts.vhdl_bitstring {
+            return write_bitstring(out, name, &words, output_width, vector);
+        }
+        writeln!(
+            out,
+            "    type font_rom is array(0 to {}) of {}({} downto 0);",
+            words.len() - 1,
+            vector,
+            output_width - 1
+        )?;
+        writeln!(out, "    constant FONT : font_rom := (")?;
+        for (i, word) in words.iter().enumerate() {
+            let literal = if output_width.is_multiple_of(4) {
+                format!("x\"{}\"", hex_digits(word, opts))
+            } else {
+                let bits: String = word
+                    .iter()
+                    .map(|&bit| if bit { '1' } else { '0' })
+                    .collect();
+                format!("\"{}\"", bits)
+            };
+            let separator = if i + 1 == words.len() { "" } else { "," };
+            writeln!(out, "        {}{}", literal, separator)?;
+        }
+        writeln!(out, "    );")?;
+        writeln!(out, "end package {};", name)
+    }
+}
+
+/// Writes the package contents as one std_logic_vector with glyph 0 leftmost, plus a glyph
+/// function slicing out one word, both of type `vector`.
+fn write_bitstring<W: Write>(
+    out: &mut W,
+    name: &str,
+    words: &[Vec<bool>],
+    output_width: usize,
+    vector: &str,
+) -> io::Result<()> {
+    let bits: String = words
+        .iter()
+        .flatten()
+        .map(|&bit| if bit { '1' } else { '0' })
+        .collect();
+    if bits.len() != words.len() * output_width {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "bit string has {} bits, not {} glyphs of {}",
+                bits.len(),
+                words.len(),
+                output_width
+            ),
+        ));
+    }
+    writeln!(
+        out,
+        "    constant FONT : {}({} downto 0) := \"{}\";",
+        vector,
+        bits.len() - 1,
+        bits
+    )?;
+    writeln!(
+        out,
+        "    function glyph(index : natural) return {};",
+        vector
+    )?;
+    writeln!(out, "end package {};", name)?;
+    writeln!(out)?;
+    writeln!(out, "package body {} is", name)?;
+    writeln!(
+        out,
+        "    function glyph(index : natural) return {} is",
+        vector
+    )?;
+    writeln!(out, "    begin")?;
+    writeln!(
+        out,
+        "        return FONT(FONT'high - index * {} downto FONT'high - index * {} - {});",
+        output_width,
+        output_width,
+        output_width - 1
+    )?;
+    writeln!(out, "    end function glyph;")?;
+    writeln!(out, "end package body {};", name)
+}
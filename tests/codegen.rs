@@ -0,0 +1,495 @@
+mod common;
+
+use common::{emit, parse, psf2, sample_font, temp_path};
+use psf2verilog::{
+    collect_notes, format_names, langs, named, rle_decode, rle_encode, Compression, Endian,
+    FoldCase, Format, Lang, Note, Options, BACKENDS,
+};
+use std::fs;
+
+#[test]
+fn output_is_deterministic() {
+    let psf = parse(&sample_font());
+    let opts = Options {
+        comment: true,
+        emit_lookup: true,
+        fold_case: Some(FoldCase::Lower),
+        ..Options::default()
+    };
+    let first = emit(&psf, &opts);
+    assert!(!first.is_empty());
+    assert_eq!(first, emit(&psf, &opts));
+    assert_eq!(first, emit(&parse(&sample_font()), &opts));
+}
+
+#[test]
+fn word_endian_reverses_bytes() {
+    let psf = parse(&psf2(16, 1, &[0x12, 0x34, 0xAB, 0xCD], 2, None));
+    let big = emit(&psf, &Options::default());
+    assert!(big.contains("16'h1234"));
+    assert!(big.contains("16'hABCD"));
+
+    let hex_path = temp_path("word-endian.hex");
+    let little = Options {
+        word_endian: Endian::Little,
+        format: Format::Memh,
+        init_file: Some(hex_path.to_string_lossy().into_owned()),
+        ..Options::default()
+    };
+    emit(&psf, &little);
+    let hex = fs::read_to_string(&hex_path).unwrap();
+    fs::remove_file(&hex_path).unwrap();
+    assert_eq!(hex.split_whitespace().collect::<Vec<_>>(), ["3412", "CDAB"]);
+
+    let little = Options {
+        word_endian: Endian::Little,
+        ..Options::default()
+    };
+    let little = emit(&psf, &little);
+    assert!(little.contains("16'h3412"));
+    assert!(little.contains("16'hCDAB"));
+}
+
+#[test]
+fn bit_order_reverses_each_byte() {
+    // An "L": the stroke down the left edge must move to the low bits
+    let psf = parse(&psf2(8, 3, &[0x80, 0x80, 0xF0], 1, None));
+    for (format, lang) in [
+        (Format::Verilog, Lang::Verilog),
+        (Format::RawHex, Lang::Verilog),
+        (Format::Oneline, Lang::Verilog),
+        (Format::Verilog, Lang::Python),
+    ] {
+        let msb = Options {
+            format,
+            lang,
+            ..Options::default()
+        };
+        assert!(emit(&psf, &msb).contains("8080F0"));
+        let lsb = Options {
+            lsb_first: true,
+            ..msb
+        };
+        let lsb = emit(&psf, &lsb);
+        assert!(lsb.contains("01010F"), "{:?} {:?}: {}", format, lang, lsb);
+    }
+}
+
+#[test]
+fn collapse_report_is_returned_not_printed() {
+    let psf = parse(&psf2(8, 1, &[0, 0, 0, 0xFF], 4, None));
+    let opts = Options {
+        collapse: true,
+        ..Options::default()
+    };
+    let (output, notes) = collect_notes(|| emit(&psf, &opts));
+    assert!(output.contains(">= 2'b00 && codepoint <= 2'b10"));
+    assert_eq!(
+        notes,
+        [Note::Collapsed {
+            module: "glyphmap".to_string(),
+            words: 4,
+            arms: 2
+        }]
+    );
+    assert_eq!(notes[0].to_string(), "collapsed 4 arms into 2");
+}
+
+#[test]
+fn lookup_of_a_one_glyph_font() {
+    let psf = parse(&psf2(8, 1, &[0x81], 1, Some(b"A\xFF")));
+    let opts = Options {
+        emit_lookup: true,
+        ..Options::default()
+    };
+    assert!(emit(&psf, &opts)
+        .contains("module glyphlookup ( input wire [6:0] unicode, output wire [0:0] codepoint );"));
+}
+
+#[test]
+fn lookup_matches_every_code_point_of_a_glyph() {
+    let psf = parse(&psf2(8, 1, &[0x81, 0x42], 2, Some(b"ab\xFFZ\xFF")));
+    let opts = Options {
+        emit_lookup: true,
+        hex_lowercase: true,
+        ..Options::default()
+    };
+    let output = emit(&psf, &opts);
+    assert!(output.contains("assign codepoint = unicode == 7'h61 || unicode == 7'h62 ? 1'b0\n"));
+    assert!(output.contains("    : unicode == 7'h5a ? 1'b1\n"));
+}
+
+#[test]
+fn one_glyph_font_gets_a_one_bit_address() {
+    let psf = parse(&psf2(8, 1, &[0x81], 1, None));
+    let dual_port = Options {
+        dual_port: true,
+        ..Options::default()
+    };
+    assert!(emit(&psf, &dual_port).contains("input wire [0:0] codepoint_a"));
+    let init_file = temp_path("one-glyph.hex");
+    let memh = Options {
+        format: Format::Memh,
+        init_file: Some(init_file.to_string_lossy().into_owned()),
+        ..Options::default()
+    };
+    assert!(emit(&psf, &memh).contains("parameter ADDR_WIDTH = 1,"));
+    let _ = fs::remove_file(init_file);
+}
+
+#[test]
+fn listed_formats_and_langs_all_resolve() {
+    let formats: Vec<&str> = format_names().collect();
+    let names: Vec<&str> = Format::ALL.iter().map(Format::name).collect();
+    assert_eq!(formats, names);
+    assert_eq!(langs(), Lang::ALL);
+    for name in formats {
+        assert_eq!(named(name).unwrap().name(), name);
+    }
+    assert!(named("verilog2").is_none());
+}
+
+#[test]
+fn every_backend_writes_something() {
+    let psf = parse(&sample_font());
+    let init_file = temp_path("every-backend.init");
+    let opts = Options {
+        init_file: Some(init_file.to_string_lossy().into_owned()),
+        ..Options::default()
+    };
+    for backend in BACKENDS {
+        let mut out = vec![];
+        backend.emit(&psf, &opts, &mut out).unwrap();
+        assert!(!out.is_empty(), "{} wrote nothing", backend.name());
+    }
+    let _ = fs::remove_file(init_file);
+}
+
+#[test]
+fn memh_depth_matches_the_hex_file() {
+    let psf = parse(&sample_font());
+    let init_file = temp_path("memh-depth.hex");
+    let opts = Options {
+        format: Format::Memh,
+        init_file: Some(init_file.to_string_lossy().into_owned()),
+        ..Options::default()
+    };
+    let module = emit(&psf, &opts);
+    let depth: usize = module
+        .split("parameter DEPTH = ")
+        .nth(1)
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|depth| depth.parse().ok())
+        .expect("no DEPTH parameter");
+    let hex = fs::read_to_string(&init_file).unwrap();
+    let _ = fs::remove_file(init_file);
+    assert_eq!(depth, 4);
+    assert_eq!(hex.lines().count(), depth);
+}
+
+#[test]
+fn rle_round_trips() {
+    let mut bits = vec![true; 300];
+    bits.extend([false, true, true, false, false, false]);
+    bits.extend(vec![false; 129]);
+    let symbols = rle_encode(&bits);
+    assert_eq!(symbols[..3], [0xFF, 0xFF, 0x80 | 43]);
+    assert_eq!(rle_decode(&symbols), bits);
+    assert!(rle_decode(&rle_encode(&[])).is_empty());
+}
+
+#[test]
+fn compress_rejects_options_the_decoder_ignores() {
+    let psf = parse(&sample_font());
+    let compressed = |opts: Options| Options {
+        compress: Some(Compression::Rle),
+        ..opts
+    };
+    assert!(psf.check_options(&compressed(Options::default())).is_ok());
+    for opts in [
+        Options {
+            invert_address: true,
+            ..Options::default()
+        },
+        Options {
+            with_reset: true,
+            ..Options::default()
+        },
+        Options {
+            with_enable: true,
+            ..Options::default()
+        },
+        Options {
+            signed_output: true,
+            ..Options::default()
+        },
+    ] {
+        assert!(psf.check_options(&compressed(opts)).is_err());
+    }
+}
+
+#[test]
+fn compress_one_bit_glyphs() {
+    let psf = parse(&psf2(1, 1, &[0x80, 0x00], 2, None));
+    let opts = Options {
+        compress: Some(Compression::Rle),
+        trim_padding_bits: true,
+        ..Options::default()
+    };
+    let output = emit(&psf, &opts);
+    assert!(output.contains("output reg [0:0] glyph, output reg done );"));
+    assert!(output.contains("        glyph <= symbol[7];\n"));
+}
+
+#[test]
+fn bad_attribute_file_reports_its_line() {
+    let psf = parse(&sample_font());
+    let path = temp_path("bad_attributes.txt");
+    fs::write(&path, "01\n02\n1FF\n04\n").unwrap();
+    let opts = Options {
+        emit_attr_rom: true,
+        attr_file: Some(path.to_str().unwrap().to_string()),
+        ..Options::default()
+    };
+    let err = psf.write_verilog(&opts, &mut vec![]).unwrap_err();
+    let _ = fs::remove_file(&path);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{} line 3: \"1FF\" is not a 8-bit hex attribute",
+            path.display()
+        )
+    );
+}
+
+#[test]
+fn struct_packs_rows_bottom_first() {
+    let psf = parse(&sample_font());
+    let opts = Options {
+        emit_struct: true,
+        ..Options::default()
+    };
+    let output = emit(&psf, &opts);
+    assert!(output.contains("    logic [7:0][7:0] row;\n"));
+    assert!(output.contains(
+        "        2'b01: glyph_rows = {8'h80, 8'h01, 8'h02, 8'h04, 8'h08, 8'h10, 8'h20, 8'h40};\n"
+    ));
+}
+
+#[test]
+fn pad_width_to_byte_undoes_trim_padding_bits() {
+    let psf = parse(&psf2(6, 2, &[0xFC, 0x84], 1, None));
+    let emit_with = |flags: &[&str]| {
+        let args = flags.iter().chain(&["font.psf"]).map(|arg| arg.to_string());
+        emit(&psf, &Options::parse(args).unwrap())
+    };
+    assert!(emit_with(&[]).contains("output wire [15:0] glyph"));
+    assert!(emit_with(&["--trim-padding-bits"]).contains("output wire [11:0] glyph"));
+    let padded = emit_with(&["--trim-padding-bits", "--pad-width-to-byte"]);
+    assert!(padded.contains("output wire [15:0] glyph"));
+    assert!(padded.contains("16'hFC84"));
+}
+
+#[test]
+fn delta_rejects_transforms() {
+    let psf = parse(&sample_font());
+    let delta = |flags: &[&str]| {
+        let args = ["delta", "old.psf", "new.psf"].iter().chain(flags);
+        Options::parse(args.map(|arg| arg.to_string())).unwrap()
+    };
+    assert!(psf.check_options(&delta(&[])).is_ok());
+    for flags in [
+        &["--trim-width"][..],
+        &["--rotate", "180"],
+        &["--pad-to", "8"],
+    ] {
+        assert!(psf.check_options(&delta(flags)).is_err(), "{:?}", flags);
+    }
+}
+
+#[test]
+fn split_files_go_next_to_the_output() {
+    let psf = parse(&sample_font());
+    let dir = temp_path("split-files");
+    fs::create_dir_all(&dir).unwrap();
+    let opts = Options {
+        split_files: Some(2),
+        output: Some(dir.join("glyphmap.v").to_string_lossy().into_owned()),
+        ..Options::default()
+    };
+    assert!(emit(&psf, &opts).contains("glyphmap_part1 part1"));
+    let part = fs::read_to_string(dir.join("charmap_1.v")).unwrap();
+    let _ = fs::remove_dir_all(dir);
+    assert!(part.contains("module glyphmap_part1"));
+}
+
+#[test]
+fn reset_loads_the_blank_value() {
+    let psf = parse(&sample_font());
+    let opts = Options {
+        with_reset: true,
+        blank_value: Some(0xFF),
+        ..Options::default()
+    };
+    assert!(emit(&psf, &opts).contains("glyph <= 64'hFFFFFFFFFFFFFFFF;"));
+}
+
+#[test]
+fn selectable_one_glyph_fonts() {
+    let psf = parse(&psf2(8, 1, &[0x81], 1, None));
+    let mut out = vec![];
+    psf.write_selectable(std::slice::from_ref(&psf), &Options::default(), &mut out)
+        .unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("input wire [0:0] font_sel, input wire [0:0] codepoint,"));
+}
+
+#[test]
+fn rom_file_module_loads_the_hex_written_beside_it() {
+    let psf = parse(&sample_font());
+    let dir = temp_path("rom-file");
+    fs::create_dir_all(&dir).unwrap();
+    let base = dir.join("font").to_string_lossy().into_owned();
+    let opts = Options {
+        format: Format::Memh,
+        emit_rom_file: Some(base.clone()),
+        ..Options::default()
+    };
+    psf.write_rom_files(&opts, &base).unwrap();
+    let module = fs::read_to_string(dir.join("font.v")).unwrap();
+    let init_file = module
+        .split("INIT_FILE = \"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("no INIT_FILE parameter")
+        .to_string();
+    let hex_exists = dir.join(&init_file).is_file();
+    let left = files_in(&dir);
+    let _ = fs::remove_dir_all(dir);
+    assert_eq!(init_file, "font.hex");
+    assert!(hex_exists);
+    assert_eq!(left, ["font.hex", "font.v"]);
+}
+
+fn rom_file_dir(name: &str) -> (std::path::PathBuf, String, Options) {
+    let dir = temp_path(name);
+    fs::create_dir_all(&dir).unwrap();
+    let base = dir.join("font").to_string_lossy().into_owned();
+    let opts = Options {
+        format: Format::Memh,
+        emit_rom_file: Some(base.clone()),
+        emit_header: true,
+        ..Options::default()
+    };
+    (dir, base, opts)
+}
+
+fn files_in(dir: &std::path::Path) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn rom_file_header_goes_beside_the_module() {
+    let psf = parse(&sample_font());
+    let (dir, base, opts) = rom_file_dir("rom-file-header");
+    psf.write_rom_files(&opts, &base).unwrap();
+    let left = files_in(&dir);
+    let header = fs::read_to_string(dir.join("glyphmap.vh")).unwrap();
+    let _ = fs::remove_dir_all(dir);
+    assert_eq!(left, ["font.hex", "font.v", "glyphmap.vh"]);
+    assert!(header.contains("`define FONT_COUNT 4\n"));
+}
+
+#[test]
+fn failed_rom_file_keeps_files_it_did_not_create() {
+    let psf = parse(&sample_font());
+    let (dir, base, opts) = rom_file_dir("rom-file-failed");
+    fs::write(dir.join("font.hex"), "old\n").unwrap();
+    // A non-empty directory in the module's place makes its rename fail last
+    fs::create_dir_all(dir.join("font.v").join("busy")).unwrap();
+    let written = psf.write_rom_files(&opts, &base);
+    let left = files_in(&dir);
+    let _ = fs::remove_dir_all(dir);
+    assert!(written.is_err());
+    assert_eq!(left, ["font.hex", "font.v"]);
+}
+
+#[test]
+fn registered_output_is_nonblocking_by_default() {
+    let psf = parse(&sample_font());
+    for opts in [
+        Options {
+            with_enable: true,
+            ..Options::default()
+        },
+        Options {
+            with_reset: true,
+            ..Options::default()
+        },
+        Options {
+            pipeline: Some(1),
+            ..Options::default()
+        },
+    ] {
+        let module = emit(&psf, &opts);
+        let sequential = module
+            .split("always @(posedge clk)")
+            .nth(1)
+            .expect("no sequential block");
+        assert!(sequential.contains("glyph <= "), "{}", module);
+        assert!(!sequential.contains("glyph = "), "{}", module);
+    }
+}
+
+#[test]
+fn hashed_miss_is_the_blank_value() {
+    let psf = parse(&sample_font());
+    let mut out = vec![];
+    let opts = Options {
+        blank_value: Some(0xFF),
+        ..Options::default()
+    };
+    psf.write_hashed(&opts, &mut out).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("== unicode ? entry[63:0] : 64'hFFFFFFFFFFFFFFFF;"));
+}
+
+#[test]
+fn one_emit_mode_at_a_time() {
+    let psf = parse(&sample_font());
+    let two_modes = Options {
+        emit_hashed: true,
+        emit_column_scan: true,
+        ..Options::default()
+    };
+    assert!(psf.check_options(&two_modes).is_err());
+    for opts in [
+        Options {
+            emit_char_row: true,
+            ..Options::default()
+        },
+        Options {
+            emit_column_scan: true,
+            ..Options::default()
+        },
+        Options {
+            emit_hashed: true,
+            ..Options::default()
+        },
+    ] {
+        assert!(psf.check_options(&opts).is_ok());
+        let validated = Options {
+            format: Format::RawHex,
+            validate_roundtrip: true,
+            ..opts
+        };
+        assert!(psf.check_options(&validated).is_err());
+    }
+}
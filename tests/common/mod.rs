@@ -0,0 +1,80 @@
+#![allow(dead_code)]
+
+use psf2verilog::{backend, Options, PSF};
+use std::io::Cursor;
+
+pub const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+pub const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+
+/// A PSF2 header for `glyphs` glyphs of `charsize` bytes, with the unicode table flag set
+/// when `has_table`.
+pub fn psf2_header(
+    glyphs: u32,
+    charsize: u32,
+    width: u32,
+    height: u32,
+    has_table: bool,
+) -> Vec<u8> {
+    let mut font = PSF2_MAGIC.to_vec();
+    for field in [0, 32, has_table as u32, glyphs, charsize, height, width] {
+        font.extend_from_slice(&field.to_le_bytes());
+    }
+    font
+}
+
+/// A PSF2 font of the given glyph bytes followed by `table`, flagged as having a table when
+/// there is one.
+pub fn psf2(width: u32, height: u32, bitmap: &[u8], glyphs: u32, table: Option<&[u8]>) -> Vec<u8> {
+    let charsize = bitmap.len() as u32 / glyphs;
+    let mut font = psf2_header(glyphs, charsize, width, height, table.is_some());
+    font.extend_from_slice(bitmap);
+    font.extend_from_slice(table.unwrap_or_default());
+    font
+}
+
+/// A PSF1 font with the given mode byte, glyph bytes and unicode table bytes.
+pub fn psf1(mode: u8, height: u8, bitmap: &[u8], table: &[u8]) -> Vec<u8> {
+    let mut font = PSF1_MAGIC.to_vec();
+    font.extend_from_slice(&[mode, height]);
+    font.extend_from_slice(bitmap);
+    font.extend_from_slice(table);
+    font
+}
+
+/// Four 8x8 glyphs, each a diagonal stroke shifted one pixel further right, mapped to 'A'..'D'.
+pub fn sample_font() -> Vec<u8> {
+    let bitmap: Vec<u8> = (0..4 * 8).map(|i| 0x80 >> ((i / 8 + i % 8) % 8)).collect();
+    psf2(8, 8, &bitmap, 4, Some(b"A\xFFB\xFFC\xFFD\xFF"))
+}
+
+pub fn parse(font: &[u8]) -> PSF {
+    PSF::parse_reader(Cursor::new(font)).expect("font should parse")
+}
+
+/// The output of the backend `opts` selects.
+pub fn emit(psf: &PSF, opts: &Options) -> String {
+    let mut out = vec![];
+    backend(opts)
+        .emit(psf, opts, &mut out)
+        .expect("emit failed");
+    String::from_utf8(out).expect("output is not UTF-8")
+}
+
+/// The unicode table of `psf` as written by `--embed-table-comment`, one line per glyph
+/// with code points, e.g. "1: U+0042, sequences U+0043 U+0044".
+pub fn table_lines(psf: &PSF) -> Vec<String> {
+    let opts = Options {
+        embed_table_comment: true,
+        ..Options::default()
+    };
+    emit(psf, &opts)
+        .lines()
+        .filter_map(|line| line.strip_prefix("//   "))
+        .map(|line| line.trim_start().to_string())
+        .collect()
+}
+
+/// A path in the system temp directory unique to this test process.
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("psf2verilog-{}-{}", std::process::id(), name))
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{emit, parse, psf2, sample_font};
+use psf2verilog::{Format, Options, ParseError, PSF};
+
+fn round_trip(psf: &PSF) -> PSF {
+    let opts = Options {
+        format: Format::Json,
+        ..Options::default()
+    };
+    PSF::from_json(emit(psf, &opts).as_bytes()).expect("json should parse")
+}
+
+#[test]
+fn json_round_trips() {
+    let psf = parse(&sample_font());
+    assert_eq!(round_trip(&psf), psf);
+}
+
+#[test]
+fn json_round_trips_sequences_and_no_table() {
+    let sequences = parse(&psf2(
+        12,
+        2,
+        &[0xAB; 8],
+        2,
+        Some(b"\xC5\x81\xFEAB\xFF\xFEC\xFF"),
+    ));
+    assert_eq!(round_trip(&sequences), sequences);
+    let untabled = parse(&psf2(12, 2, &[0xAB; 8], 2, None));
+    assert_eq!(round_trip(&untabled), untabled);
+}
+
+#[test]
+fn json_with_huge_glyphs_is_an_error() {
+    let font = |size: u32| {
+        let json = format!(
+            r#"{{"version": "PSF2", "width": {0}, "height": {0}, "glyphs": ["00"], "table": null}}"#,
+            size
+        );
+        PSF::from_json(json.as_bytes())
+    };
+    assert!(matches!(font(4000000000), Err(ParseError::BadGeometry(_))));
+    assert!(matches!(font(60000), Err(ParseError::MalformedJSON(_))));
+}
@@ -0,0 +1,391 @@
+mod common;
+
+use common::{parse, psf1, psf2, psf2_header, sample_font, table_lines, temp_path};
+use psf2verilog::{
+    parse_pbm, subset_codepoints, FontGeometry, Options, ParseError, Version, Warning, PSF,
+};
+use std::fs;
+use std::io::Cursor;
+
+#[test]
+fn glyph_count_psf1() {
+    let psf = parse(&psf1(0x00, 8, &[0; 256 * 8], &[]));
+    assert_eq!(psf.glyph_count(), 256);
+    assert_eq!(psf.geometry().glyph_count, 256);
+    assert!(psf.glyph_bytes(255).is_some());
+    assert!(psf.glyph_bytes(256).is_none());
+
+    let psf = parse(&psf1(0x01, 8, &[0; 512 * 8], &[]));
+    assert_eq!(psf.glyph_count(), 512);
+}
+
+#[test]
+fn glyph_count_psf2() {
+    let psf = parse(&psf2(8, 4, &[0; 3 * 4], 3, None));
+    assert_eq!(psf.glyph_count(), 3);
+    assert_eq!(psf.geometry().glyph_count, 3);
+    assert!(psf.glyph_bytes(3).is_none());
+}
+
+#[test]
+fn psf1_table_ending_in_a_sequence_keeps_the_entry() {
+    let mut table = vec![];
+    for codepoint in [0x141u16, 0xFFFF, 0x142, 0xFFFE, 0x143, 0x144] {
+        table.extend_from_slice(&codepoint.to_le_bytes());
+    }
+    let font = psf1(0x06, 1, &[0; 256], &table);
+    let (psf, warnings) =
+        PSF::parse_reader_with_warnings(Cursor::new(&font), &Options::default()).unwrap();
+    assert_eq!(warnings, [Warning::TruncatedTable(2)]);
+    assert_eq!(
+        table_lines(&psf),
+        ["0: U+0141", "1: U+0142, sequences U+0143 U+0144"]
+    );
+
+    // Half a code point at the end is dropped rather than read past
+    table.push(0x01);
+    let psf = parse(&psf1(0x06, 1, &[0; 256], &table));
+    assert_eq!(
+        table_lines(&psf),
+        ["0: U+0141", "1: U+0142, sequences U+0143 U+0144"]
+    );
+}
+
+#[test]
+fn psf2_table_ending_in_a_sequence_keeps_the_entry() {
+    let psf = parse(&psf2(8, 1, &[0; 3], 3, Some(b"A\xFFB\xFECD")));
+    assert_eq!(
+        table_lines(&psf),
+        ["0: U+0041", "1: U+0042, sequences U+0043 U+0044"]
+    );
+}
+
+#[test]
+fn empty_table_is_no_table() {
+    let fonts = [
+        psf2(8, 1, &[0; 2], 2, Some(b"")),
+        psf2(8, 1, &[0; 2], 2, Some(b"\n")),
+        psf1(0x02, 1, &[0; 256], &[]),
+    ];
+    for font in &fonts {
+        let (psf, warnings) =
+            PSF::parse_reader_with_warnings(Cursor::new(font), &Options::default()).unwrap();
+        assert_eq!(warnings[0], Warning::EmptyTable);
+        assert!(table_lines(&psf).is_empty());
+        let checks = psf.check(&warnings, &Options::default());
+        assert!(checks.contains(&("table length", Ok(()))));
+    }
+}
+
+fn parse_err(font: &[u8]) -> ParseError {
+    PSF::parse_reader(Cursor::new(font)).unwrap_err()
+}
+
+#[test]
+fn undecodable_table_is_an_error() {
+    let utf8 = psf2(8, 1, &[0; 2], 2, Some(b"A\xFF\xC3\xFF"));
+    assert!(matches!(parse_err(&utf8), ParseError::MalformedTable(1, _)));
+    let utf16 = psf1(0x02, 1, &[0; 256], &[0x00, 0xD8, 0xFF, 0xFF]);
+    assert!(matches!(
+        parse_err(&utf16),
+        ParseError::MalformedTable(0, _)
+    ));
+}
+
+#[test]
+fn oversized_bitmap_is_an_error() {
+    let overflow = psf2_header(0x1_0000, 0x1_0001, 8, 1, false);
+    assert!(matches!(parse_err(&overflow), ParseError::BadGeometry(_)));
+    let short = psf2_header(0x1000, 0x1000, 8, 1, false);
+    assert!(matches!(parse_err(&short), ParseError::IoError(_)));
+}
+
+#[test]
+fn font_without_glyphs_fails_the_check() {
+    let psf = parse(&psf2_header(0, 1, 8, 1, false));
+    let checks = psf.check(&[], &Options::default());
+    assert!(checks
+        .iter()
+        .any(|(name, result)| *name == "glyph count" && result.is_err()));
+    assert!(psf.check_options(&Options::default()).is_err());
+}
+
+#[test]
+fn entry_of_only_a_sequence_has_nothing_represented() {
+    let psf = parse(&psf2(8, 1, &[0; 2], 2, Some(b"\xFEAB\xFFC\xFF")));
+    assert_eq!(
+        table_lines(&psf),
+        ["0: sequences U+0041 U+0042", "1: U+0043"]
+    );
+    let psf = parse(&psf1(
+        0x02,
+        1,
+        &[0; 256],
+        &[0xFE, 0xFF, 0x41, 0, 0x42, 0, 0xFF, 0xFF],
+    ));
+    assert_eq!(table_lines(&psf)[0], "0: sequences U+0041 U+0042");
+}
+
+#[test]
+fn glyph_bytes_stops_at_the_last_glyph() {
+    let psf = parse(&sample_font());
+    assert_eq!(
+        psf.glyph_bytes(3),
+        Some(&[0x10, 0x08, 0x04, 0x02, 0x01, 0x80, 0x40, 0x20][..])
+    );
+    assert_eq!(psf.glyph_bytes(4), None);
+}
+
+#[test]
+fn subset_file_with_a_byte_order_mark() {
+    let codepoints = subset_codepoints("\u{FEFF}AB\r\nA\u{1F600}\n".as_bytes()).unwrap();
+    assert_eq!(codepoints.into_iter().collect::<String>(), "AB\u{1F600}");
+    assert!(subset_codepoints(b"\xEF\xBB\xBFA\xFF").is_err());
+}
+
+#[test]
+fn psf1_512_glyphs_without_a_table() {
+    let bitmap: Vec<u8> = (0..512 * 2).map(|i| (i / 2) as u8).collect();
+    let (psf, warnings) = PSF::parse_reader_with_warnings(
+        Cursor::new(psf1(0x01, 2, &bitmap, &[])),
+        &Options::default(),
+    )
+    .unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(psf.glyph_count(), 512);
+    assert_eq!(psf.glyph_bytes(511), Some(&[0xFF, 0xFF][..]));
+    assert_eq!(psf.glyph_bytes(512), None);
+    assert!(table_lines(&psf).is_empty());
+}
+
+#[test]
+fn extra_pad_byte_is_read_and_ignored() {
+    let (psf, warnings) = PSF::parse_reader_with_warnings(
+        Cursor::new(psf2(8, 2, &[0x81, 0x42, 0xFF, 0x18, 0x24, 0xFF], 2, None)),
+        &Options::default(),
+    )
+    .unwrap();
+    assert_eq!(warnings, [Warning::PaddedGlyphs(3, 2)]);
+    assert_eq!(psf.glyph_bytes(1), Some(&[0x18, 0x24, 0xFF][..]));
+    let glyph = psf.glyph(1);
+    let rows: Vec<Vec<bool>> = (0..2)
+        .map(|row| (0..8).map(|col| glyph.pixel(row, col)).collect())
+        .collect();
+    assert_eq!(
+        rows[1],
+        [false, false, true, false, false, true, false, false]
+    );
+}
+
+#[test]
+fn glyphs_too_small_for_their_pixels_are_an_error() {
+    let short = psf2(8, 2, &[0x81, 0x42], 2, None);
+    assert!(matches!(parse_err(&short), ParseError::BadGeometry(_)));
+    let empty = psf2_header(1, 1, 0, 1, false);
+    assert!(matches!(parse_err(&empty), ParseError::BadGeometry(_)));
+    let flat = psf1(0x00, 0, &[], &[]);
+    assert!(matches!(parse_err(&flat), ParseError::BadGeometry(_)));
+}
+
+#[test]
+fn geometry_of_both_versions() {
+    let v1 = parse(&psf1(0x00, 14, &[0; 256 * 14], &[]));
+    assert_eq!(
+        v1.geometry(),
+        FontGeometry {
+            version: Version::PSF1,
+            width: 8,
+            height: 14,
+            charsize: 14,
+            glyph_count: 256,
+        }
+    );
+    let v2 = parse(&psf2(12, 3, &[0; 5 * 7], 5, None));
+    assert_eq!(
+        v2.geometry(),
+        FontGeometry {
+            version: Version::PSF2,
+            width: 12,
+            height: 3,
+            charsize: 7,
+            glyph_count: 5,
+        }
+    );
+}
+
+#[test]
+fn table_ending_on_a_separator_has_an_entry_per_glyph() {
+    let (psf, warnings) = PSF::parse_reader_with_warnings(
+        Cursor::new(psf2(8, 1, &[0; 3], 3, Some(b"A\xFFB\xFF\xFF"))),
+        &Options::default(),
+    )
+    .unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(table_lines(&psf), ["0: U+0041", "1: U+0042"]);
+    let checks = psf.check(&warnings, &Options::default());
+    assert!(checks.contains(&("table length", Ok(()))));
+}
+
+#[test]
+fn trailing_newline_is_a_stray_byte() {
+    let mut font = sample_font();
+    font.push(b'\n');
+    let (psf, warnings) =
+        PSF::parse_reader_with_warnings(Cursor::new(font), &Options::default()).unwrap();
+    assert_eq!(warnings, [Warning::StrayByte(b'\n')]);
+    assert_eq!(
+        table_lines(&psf),
+        ["0: U+0041", "1: U+0042", "2: U+0043", "3: U+0044"]
+    );
+}
+
+#[test]
+fn psf1_truncated_table_ending_in_an_ascii_code_point() {
+    let psf = parse(&psf1(0x02, 1, &[0; 256], &[0x41, 0, 0xFF, 0xFF, 0x42, 0]));
+    assert_eq!(table_lines(&psf), ["0: U+0041", "1: U+0042"]);
+}
+
+#[test]
+fn lazy_font_reads_the_same_glyphs() {
+    let font = sample_font();
+    let psf = parse(&font);
+    let mut lazy = PSF::open_lazy(Cursor::new(&font)).unwrap();
+    assert_eq!(lazy.geometry(), psf.geometry());
+    for index in 0..5 {
+        assert_eq!(
+            lazy.glyph_bytes(index).unwrap().as_deref(),
+            psf.glyph_bytes(index)
+        );
+    }
+}
+
+#[test]
+fn lazy_header_past_the_end_is_an_error() {
+    let mut font = psf2_header(1, 1, 8, 1, false);
+    font[8..12].copy_from_slice(&1000u32.to_le_bytes());
+    assert!(matches!(
+        PSF::open_lazy(Cursor::new(&font)),
+        Err(ParseError::TooShort(32))
+    ));
+    assert!(matches!(parse_err(&font), ParseError::TooShort(32)));
+}
+
+#[test]
+fn compact_table_parser_reads_the_same_table() {
+    let mut stray = sample_font();
+    stray.push(0);
+    let fonts = [
+        sample_font(),
+        stray,
+        psf2(8, 1, &[0; 3], 3, Some(b"A\xFFB\xFECD")),
+        psf2(
+            8,
+            1,
+            &[0; 3],
+            3,
+            Some(b"\xFEAB\xFFC\xC5\x81\xFE\xE2\x82\xACD\xFF\xFF"),
+        ),
+        psf1(0x02, 1, &[0; 256], &[0x41, 0, 0xFF, 0xFF, 0x42, 0]),
+        psf1(
+            0x06,
+            1,
+            &[0; 256],
+            &[0x41, 0, 0xFE, 0xFF, 0x42, 0, 0x01, 0x03, 0xFF, 0xFF],
+        ),
+    ];
+    let compact = Options {
+        compact_table: true,
+        ..Options::default()
+    };
+    for font in &fonts {
+        let simple = PSF::parse_reader_with_warnings(Cursor::new(font), &Options::default());
+        let compact = PSF::parse_reader_with_warnings(Cursor::new(font), &compact);
+        assert_eq!(simple.unwrap(), compact.unwrap());
+    }
+    let bad = psf2(8, 1, &[0; 2], 2, Some(b"A\xFF\xC3\xFF"));
+    assert!(matches!(
+        PSF::parse_reader_with_warnings(Cursor::new(&bad), &compact),
+        Err(ParseError::MalformedTable(1, _))
+    ));
+}
+
+fn bdf(bounding_box: &str) -> Vec<u8> {
+    format!(
+        "STARTFONT 2.1\nFONTBOUNDINGBOX {}\nCHARS 1\nSTARTCHAR A\nENCODING 65\n\
+         BBX 8 2 0 0\nBITMAP\n80\n40\nENDCHAR\nENDFONT\n",
+        bounding_box
+    )
+    .into_bytes()
+}
+
+#[test]
+fn bdf_bounding_box_out_of_range_is_an_error() {
+    assert_eq!(table_lines(&parse(&bdf("8 2 0 0"))), ["0: U+0041"]);
+    for bounding_box in ["99999999 99999999 0 0", "0 2 0 0"] {
+        let err = parse_err(&bdf(bounding_box));
+        assert!(
+            matches!(
+                err,
+                ParseError::MalformedBDF(_) | ParseError::BadGeometry(_)
+            ),
+            "{:?}",
+            err
+        );
+    }
+    let overflow = parse(&bdf("8 8 0 2147483647"));
+    assert_eq!(overflow.glyph_count(), 1);
+}
+
+fn option_err(args: &[&str]) -> ParseError {
+    match Options::parse(args.iter().map(|a| a.to_string())) {
+        Err(err) => err,
+        Ok(_) => panic!("{:?} parsed", args),
+    }
+}
+
+#[test]
+fn numbers_wider_than_u32_are_invalid_arguments() {
+    for flag in [
+        "--cell-width",
+        "--cell-height",
+        "--bitplanes",
+        "--pad-to",
+        "--max-width",
+        "--max-height",
+    ] {
+        match option_err(&[flag, "4294967296", "font.psf"]) {
+            ParseError::InvalidArgument(arg) => assert_eq!(arg, flag),
+            err => panic!("{:?}", err),
+        }
+    }
+}
+
+#[test]
+fn pbm_with_no_or_too_many_pixels_is_an_error() {
+    assert_eq!(
+        parse_pbm(b"P1 2 1\n1 0\n").unwrap(),
+        (2, 1, vec![true, false])
+    );
+    assert_eq!(
+        parse_pbm(b"P4 3 2\n\xA0\x40").unwrap(),
+        (3, 2, vec![true, false, true, false, true, false])
+    );
+    for image in [
+        &b"P4 0 5\n"[..],
+        b"P1 3 0\n",
+        b"P1 99999999999 99999999999\n1\n",
+        b"P4 99999999999 99999999999\n\xFF",
+    ] {
+        assert!(matches!(parse_pbm(image), Err(ParseError::MalformedPBM(_))));
+    }
+}
+
+#[test]
+fn import_glyphs_from_a_zero_width_image_is_an_error() {
+    let dir = temp_path("import-zero-width");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("U+0041.pbm"), "P4 0 5\n").unwrap();
+    let imported = PSF::import_glyphs(&dir, 0, 0);
+    let _ = fs::remove_dir_all(&dir);
+    assert!(matches!(imported, Err(ParseError::MalformedPBM(_))));
+}
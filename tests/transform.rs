@@ -0,0 +1,73 @@
+mod common;
+
+use common::{parse, psf2, sample_font, table_lines};
+
+#[test]
+fn rotate_180_by_hand() {
+    let mut psf = parse(&psf2(
+        10,
+        3,
+        &[0xC0, 0x00, 0x00, 0x40, 0xA0, 0xC0, 0x5A],
+        1,
+        None,
+    ));
+    psf.rotate_180();
+    assert_eq!(
+        psf.glyph_bytes(0),
+        Some(&[0xC1, 0x40, 0x80, 0x00, 0x00, 0xC0, 0x5A][..])
+    );
+}
+
+#[test]
+fn rotate_180_moves_every_pixel_to_the_opposite_corner() {
+    let original = parse(&sample_font());
+    let mut rotated = original.clone();
+    rotated.rotate_180();
+    assert_eq!(rotated.geometry(), original.geometry());
+    for index in 0..4 {
+        let (before, after) = (original.glyph(index), rotated.glyph(index));
+        for row in 0..8 {
+            for col in 0..8 {
+                assert_eq!(after.pixel(row, col), before.pixel(7 - row, 7 - col));
+            }
+        }
+    }
+}
+
+#[test]
+fn strip_control_chars_cleans_the_table() {
+    let mut psf = parse(&psf2(
+        8,
+        1,
+        &[0; 2],
+        2,
+        Some(b"A\r\n\xFF\rB\xFEC\r\x01D\xFF"),
+    ));
+    assert_eq!(psf.strip_control_chars(), 5);
+    assert_eq!(
+        table_lines(&psf),
+        ["0: U+0041", "1: U+0042, sequences U+0043 U+0044"]
+    );
+}
+
+#[test]
+fn trim_width_keeps_the_columns_in_use() {
+    let mut psf = parse(&psf2(
+        16,
+        2,
+        &[0x07, 0xC0, 0x04, 0x00, 0x01, 0x00, 0x00, 0x40],
+        2,
+        None,
+    ));
+    psf.trim_width();
+    assert_eq!(psf.geometry().width, 5);
+    assert_eq!(psf.glyph_bytes(0), Some(&[0xF8, 0x80][..]));
+    assert_eq!(psf.glyph_bytes(1), Some(&[0x20, 0x08][..]));
+}
+
+#[test]
+fn normalize_centers_with_the_odd_pixel_right_and_below() {
+    let mut psf = parse(&psf2(8, 7, &[0xE0, 0xE0, 0, 0, 0, 0, 0], 1, None));
+    psf.normalize();
+    assert_eq!(psf.glyph_bytes(0), Some(&[0, 0, 0x38, 0x38, 0, 0, 0][..]));
+}